@@ -0,0 +1,327 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::{anyhow, bail, Result};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use options_program_sdk::common::{ExerciseStyle, ExpirySetting, OptionType, PricingModel, SpotDeviation};
+use options_program_sdk::constants::PRICE_DECIMALS;
+
+//Pyth SOL/USD price update account, cloned by the local validator (see README)
+pub const DEFAULT_PRICE_UPDATE: &str = "7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE";
+pub const MARKET_NAME_MAX_LEN: usize = 32;
+
+#[derive(Parser)]
+#[command(name = "options-cli", about = "Admin, LP and trader client for the options program")]
+pub struct Cli {
+    #[arg(long, env = "OPTIONS_RPC_URL", default_value = "http://127.0.0.1:8899", global = true)]
+    pub url: String,
+
+    //Defaults to the Solana CLI keypair, ~/.config/solana/id.json
+    #[arg(long, env = "OPTIONS_KEYPAIR", global = true)]
+    pub keypair: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Market administration and state
+    #[command(subcommand)]
+    Market(MarketCommand),
+    /// Deposit asset tokens into a market for LP tokens
+    Deposit {
+        #[arg(long)]
+        market: u16,
+        /// Asset tokens, in base units
+        #[arg(long)]
+        amount: u64,
+        #[arg(long, default_value_t = 1)]
+        min_lp_out: u64,
+    },
+    /// Burn LP tokens for their share of the market
+    Withdraw {
+        #[arg(long)]
+        market: u16,
+        /// LP tokens, in base units
+        #[arg(long)]
+        lp_tokens: u64,
+        #[arg(long, default_value_t = 1)]
+        min_amount_out: u64,
+    },
+    /// User account pages
+    #[command(subcommand)]
+    Account(AccountCommand),
+    /// Buy an option at a spot deviation or explicit strike
+    Buy(BuyArgs),
+    /// Exercise an option held in a user account page
+    Exercise {
+        #[arg(long)]
+        market: u16,
+        #[arg(long)]
+        option_id: u8,
+        /// Contracts to exercise, defaults to the whole position
+        #[arg(long)]
+        quantity: Option<u64>,
+        #[arg(long, default_value_t = 0)]
+        page: u8,
+        #[arg(long, default_value = DEFAULT_PRICE_UPDATE)]
+        price_update: Pubkey,
+    },
+    /// List the options held in every account page
+    Positions {
+        /// Defaults to the keypair owner
+        #[arg(long)]
+        owner: Option<Pubkey>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum MarketCommand {
+    /// Create a market with its vault, LP mint and fee vault
+    Create(CreateMarketArgs),
+    /// Push a new volatility surface
+    UpdateVol {
+        #[arg(long)]
+        market: u16,
+        /// 5 values (one per tenor 1H, 4H, 1D, 3D, 1W, flat across strikes) or the full 45 node surface, row by row
+        #[arg(long, value_delimiter = ',', required = true)]
+        vols_bps: Vec<u32>,
+    },
+    /// Close an empty market (fees withdrawn, LPs out) and send leftover dust to the admin
+    Close {
+        #[arg(long)]
+        market: u16,
+    },
+    /// Withdraw accrued protocol fees to the fee collector
+    WithdrawFees {
+        #[arg(long)]
+        market: u16,
+    },
+    /// Show market state, utilization and LP share price
+    Show {
+        #[arg(long)]
+        market: u16,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AccountCommand {
+    /// Create a user account page
+    Create {
+        #[arg(long, default_value_t = 0)]
+        page: u8,
+    },
+}
+
+#[derive(Args)]
+pub struct CreateMarketArgs {
+    #[arg(long)]
+    pub market: u16,
+    /// Display name, e.g. "SOL/USD"
+    #[arg(long, value_parser = parse_market_name)]
+    pub name: String,
+    #[arg(long)]
+    pub asset_mint: Pubkey,
+    /// Pyth feed id (hex) of the asset/USD price
+    #[arg(long)]
+    pub price_feed: String,
+    #[arg(long)]
+    pub fee_bps: u64,
+    /// Strike tick size in USD, e.g. 1 for SOL or 0.01 for JUP
+    #[arg(long, value_parser = parse_usd)]
+    pub strike_tick_size: u64,
+    /// Asset base units per contract, e.g. 100000000 = 0.1 SOL
+    #[arg(long)]
+    pub contract_size: u64,
+    /// 5 values (per tenor, flat across strikes) or the full 45 node surface
+    #[arg(long, value_delimiter = ',', default_value = "8000,9000,8000,7500,7000")]
+    pub vols_bps: Vec<u32>,
+    #[arg(long, default_value_t = 3600)]
+    pub max_vol_age_secs: u64,
+    #[arg(long, default_value_t = 1000)]
+    pub min_volatility_bps: u32,
+    #[arg(long, default_value_t = 30000)]
+    pub max_volatility_bps: u32,
+    #[arg(long, default_value_t = 5000)]
+    pub max_vol_change_bps: u32,
+    #[arg(long, default_value_t = 50)]
+    pub keeper_bounty_bps: u64,
+    #[arg(long, value_enum, default_value_t = PricingModelArg::BlackScholes)]
+    pub pricing_model: PricingModelArg,
+    #[arg(long, default_value_t = 60)]
+    pub max_price_age_buy_secs: u64,
+    #[arg(long, default_value_t = 90)]
+    pub max_price_age_exercise_secs: u64,
+    #[arg(long, default_value_t = 100)]
+    pub max_price_conf_bps: u64,
+    #[arg(long)]
+    pub exercise_use_ema: bool,
+    #[arg(long, default_value_t = 8000)]
+    pub min_strike_moneyness_bps: u32,
+    #[arg(long, default_value_t = 12000)]
+    pub max_strike_moneyness_bps: u32,
+    /// Bitfield of listed series: 1 daily, 2 weekly, 4 custom
+    #[arg(long, default_value_t = 0b111)]
+    pub expiry_series: u8,
+    #[arg(long, default_value_t = 8)]
+    pub expiry_hour_utc: u8,
+    #[arg(long, default_value_t = 900)]
+    pub min_time_to_expiry_secs: u64,
+    #[arg(long, value_enum, default_value_t = ExerciseStyleArg::American)]
+    pub exercise_style: ExerciseStyleArg,
+    #[arg(long, default_value_t = 200)]
+    pub sell_back_spread_bps: u64,
+}
+
+#[derive(Args)]
+pub struct BuyArgs {
+    #[arg(long)]
+    pub market: u16,
+    #[arg(long, value_enum)]
+    pub option: OptionTypeArg,
+    /// Strike as a deviation from spot
+    #[arg(long, value_enum, conflicts_with = "strike", required_unless_present = "strike")]
+    pub deviation: Option<SpotDeviationArg>,
+    /// Explicit strike in USD, must be on the market tick size and band
+    #[arg(long, value_parser = parse_usd)]
+    pub strike: Option<u64>,
+    /// daily:<offset>, weekly:<offset> or custom:<unix timestamp>
+    #[arg(long, value_parser = parse_expiry, default_value = "daily:0")]
+    pub expiry: ExpirySetting,
+    /// Contracts to buy
+    #[arg(long)]
+    pub quantity: u64,
+    /// Max premium paid, in asset base units
+    #[arg(long)]
+    pub max_premium: u64,
+    /// Spot the buy was quoted at in USD, with --max-spot-deviation-bps
+    #[arg(long, value_parser = parse_usd, requires = "max_spot_deviation_bps")]
+    pub quoted_spot: Option<u64>,
+    #[arg(long, requires = "quoted_spot")]
+    pub max_spot_deviation_bps: Option<u64>,
+    #[arg(long, default_value_t = 0)]
+    pub page: u8,
+    #[arg(long, default_value = DEFAULT_PRICE_UPDATE)]
+    pub price_update: Pubkey,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OptionTypeArg {
+    Call,
+    Put,
+}
+
+impl From<OptionTypeArg> for OptionType {
+    fn from(value: OptionTypeArg) -> Self {
+        match value {
+            OptionTypeArg::Call => OptionType::CALL,
+            OptionTypeArg::Put => OptionType::PUT,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum SpotDeviationArg {
+    N20,
+    N15,
+    N10,
+    N5,
+    P0,
+    P5,
+    P10,
+    P15,
+    P20,
+}
+
+impl From<SpotDeviationArg> for SpotDeviation {
+    fn from(value: SpotDeviationArg) -> Self {
+        match value {
+            SpotDeviationArg::N20 => SpotDeviation::N20,
+            SpotDeviationArg::N15 => SpotDeviation::N15,
+            SpotDeviationArg::N10 => SpotDeviation::N10,
+            SpotDeviationArg::N5 => SpotDeviation::N5,
+            SpotDeviationArg::P0 => SpotDeviation::P0,
+            SpotDeviationArg::P5 => SpotDeviation::P5,
+            SpotDeviationArg::P10 => SpotDeviation::P10,
+            SpotDeviationArg::P15 => SpotDeviation::P15,
+            SpotDeviationArg::P20 => SpotDeviation::P20,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum PricingModelArg {
+    Legacy,
+    BlackScholes,
+}
+
+impl From<PricingModelArg> for PricingModel {
+    fn from(value: PricingModelArg) -> Self {
+        match value {
+            PricingModelArg::Legacy => PricingModel::Legacy,
+            PricingModelArg::BlackScholes => PricingModel::BlackScholes,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExerciseStyleArg {
+    American,
+    European,
+}
+
+impl From<ExerciseStyleArg> for ExerciseStyle {
+    fn from(value: ExerciseStyleArg) -> Self {
+        match value {
+            ExerciseStyleArg::American => ExerciseStyle::American,
+            ExerciseStyleArg::European => ExerciseStyle::European,
+        }
+    }
+}
+
+//USD amount as a decimal string, to the program's 10^8 fixed point
+pub fn parse_usd(value: &str) -> Result<u64> {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    let decimals = PRICE_DECIMALS as usize;
+    if whole.is_empty() && fraction.is_empty() {
+        bail!("empty USD amount");
+    }
+    if fraction.len() > decimals {
+        bail!("USD amounts have at most {decimals} decimals");
+    }
+
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse()? };
+    let fraction: u64 = format!("{fraction:0<decimals$}").parse()?;
+
+    whole
+        .checked_mul(10_u64.pow(PRICE_DECIMALS as u32))
+        .and_then(|scaled| scaled.checked_add(fraction))
+        .ok_or_else(|| anyhow!("USD amount too large"))
+}
+
+//Market names are stored in a 32 byte string
+pub fn parse_market_name(value: &str) -> Result<String> {
+    if value.is_empty() || value.len() > MARKET_NAME_MAX_LEN {
+        bail!("market name must be 1 to {MARKET_NAME_MAX_LEN} bytes");
+    }
+
+    Ok(value.to_string())
+}
+
+pub fn parse_expiry(value: &str) -> Result<ExpirySetting> {
+    let (kind, arg) = value
+        .split_once(':')
+        .ok_or_else(|| anyhow!("expected daily:<offset>, weekly:<offset> or custom:<timestamp>"))?;
+
+    match kind {
+        "daily" => Ok(ExpirySetting::Daily { offset: arg.parse()? }),
+        "weekly" => Ok(ExpirySetting::Weekly { offset: arg.parse()? }),
+        "custom" => Ok(ExpirySetting::Custom { expiry_stamp: arg.parse()? }),
+        _ => bail!("unknown expiry series {kind}"),
+    }
+}
+
+//Formats a 10^8 fixed point USD value
+pub fn format_usd(value: u64) -> String {
+    let scale = 10_u64.pow(PRICE_DECIMALS as u32);
+    format!("{}.{:0width$}", value / scale, value % scale, width = PRICE_DECIMALS as usize)
+}
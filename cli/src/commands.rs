@@ -0,0 +1,360 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anchor_lang::prelude::Pubkey;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use anyhow::{anyhow, bail, Context, Result};
+use options_program_sdk::{
+    accounts::{decode_market, decode_user_account},
+    common::{ExerciseStyle, OptionType, SpotSlippage},
+    constants::{EUROPEAN_EXERCISE_WINDOW_SECS, EXERCISE_INTERVAL_TOLERANCE},
+    instructions::*,
+    pda::*,
+    state::{market::*, user_account::UserAccount},
+};
+use solana_rpc_client::rpc_client::RpcClient;
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    instruction::Instruction,
+    signature::{read_keypair_file, Keypair, Signature, Signer},
+    transaction::Transaction,
+};
+
+use crate::args::*;
+
+//Account pages fetched per RPC call
+const PAGES_PER_REQUEST: usize = 100;
+
+pub struct Client {
+    rpc: RpcClient,
+    payer: Keypair,
+}
+
+impl Client {
+    pub fn new(url: &str, keypair: Option<&str>) -> Result<Self> {
+        let path = match keypair {
+            Some(path) => path.to_string(),
+            None => format!("{}/.config/solana/id.json", std::env::var("HOME").context("HOME is not set")?),
+        };
+        let payer = read_keypair_file(&path).map_err(|e| anyhow!("reading keypair {path}: {e}"))?;
+
+        Ok(Client {
+            rpc: RpcClient::new_with_commitment(url.to_string(), CommitmentConfig::confirmed()),
+            payer,
+        })
+    }
+
+    fn send(&self, instructions: &[Instruction]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let tx = Transaction::new_signed_with_payer(instructions, Some(&self.payer.pubkey()), &[&self.payer], blockhash);
+        let signature = self.rpc.send_and_confirm_transaction(&tx)?;
+        println!("Signature: {signature}");
+        Ok(signature)
+    }
+
+    fn market(&self, market_ix: u16) -> Result<Market> {
+        let data = self
+            .rpc
+            .get_account_data(&find_market_address(market_ix).0)
+            .with_context(|| format!("market {market_ix} not found"))?;
+        decode_market(&data).map_err(|e| anyhow!("decoding market {market_ix}: {e}"))
+    }
+
+    //SPL token or token-2022, whichever owns the mint
+    fn token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        Ok(self.rpc.get_account(mint).with_context(|| format!("mint {mint} not found"))?.owner)
+    }
+
+    fn create_ata_ix(&self, mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+        create_associated_token_account_idempotent(&self.payer.pubkey(), &self.payer.pubkey(), mint, token_program)
+    }
+
+    pub fn run(&self, command: Command) -> Result<()> {
+        match command {
+            Command::Market(command) => self.run_market(command),
+            Command::Deposit { market, amount, min_lp_out } => {
+                let asset_mint = self.market(market)?.asset_mint;
+                let token_program = self.token_program(&asset_mint)?;
+                self.send(&[market_deposit(self.payer.pubkey(), asset_mint, token_program, DepositIx { amount, min_amount_out: min_lp_out, ix: market })])?;
+                Ok(())
+            },
+            Command::Withdraw { market, lp_tokens, min_amount_out } => {
+                let asset_mint = self.market(market)?.asset_mint;
+                let token_program = self.token_program(&asset_mint)?;
+                self.send(&[market_withdraw(self.payer.pubkey(), asset_mint, token_program, WithdrawParams { lp_tokens_to_burn: lp_tokens, min_amount_out, ix: market })])?;
+                Ok(())
+            },
+            Command::Account(AccountCommand::Create { page }) => {
+                self.send(&[create_account(self.payer.pubkey(), AccountCreateParams { page })])?;
+                println!("Account page {page}: {}", find_user_account_address(&self.payer.pubkey(), page).0);
+                Ok(())
+            },
+            Command::Buy(args) => self.buy(args),
+            Command::Exercise { market, option_id, quantity, page, price_update } => {
+                self.exercise(market, option_id, quantity, page, price_update)
+            },
+            Command::Positions { owner } => self.positions(owner.unwrap_or(self.payer.pubkey())),
+        }
+    }
+
+    fn run_market(&self, command: MarketCommand) -> Result<()> {
+        let signer = self.payer.pubkey();
+
+        match command {
+            MarketCommand::Create(args) => {
+                let token_program = self.token_program(&args.asset_mint)?;
+                let params = CreateMarketParams {
+                    fee: args.fee_bps,
+                    name: args.name,
+                    ix: args.market,
+                    price_feed: args.price_feed,
+                    vol_surface_bps: vol_surface(&args.vols_bps)?,
+                    max_vol_age_secs: args.max_vol_age_secs,
+                    min_volatility_bps: args.min_volatility_bps,
+                    max_volatility_bps: args.max_volatility_bps,
+                    max_vol_change_bps: args.max_vol_change_bps,
+                    keeper_bounty_bps: args.keeper_bounty_bps,
+                    pricing_model: args.pricing_model.into(),
+                    max_price_age_buy_secs: args.max_price_age_buy_secs,
+                    max_price_age_exercise_secs: args.max_price_age_exercise_secs,
+                    max_price_conf_bps: args.max_price_conf_bps,
+                    exercise_use_ema: args.exercise_use_ema,
+                    strike_tick_size: args.strike_tick_size,
+                    min_strike_moneyness_bps: args.min_strike_moneyness_bps,
+                    max_strike_moneyness_bps: args.max_strike_moneyness_bps,
+                    expiry_series: args.expiry_series,
+                    expiry_hour_utc: args.expiry_hour_utc,
+                    min_time_to_expiry_secs: args.min_time_to_expiry_secs,
+                    exercise_style: args.exercise_style.into(),
+                    sell_back_spread_bps: args.sell_back_spread_bps,
+                    contract_size: args.contract_size,
+                };
+                self.send(&[create_market(signer, args.asset_mint, token_program, params)])?;
+                println!("Market {}: {}", args.market, find_market_address(args.market).0);
+            },
+            MarketCommand::UpdateVol { market, vols_bps } => {
+                self.send(&[update_market_vol(signer, UpdateMarketVolParams { ix: market, vol_surface_bps: vol_surface(&vols_bps)? })])?;
+            },
+            MarketCommand::Close { market } => {
+                let asset_mint = self.market(market)?.asset_mint;
+                let token_program = self.token_program(&asset_mint)?;
+                self.send(&[
+                    self.create_ata_ix(&asset_mint, &token_program),
+                    close_market(signer, asset_mint, token_program, CloseMarketParams { ix: market }),
+                ])?;
+            },
+            MarketCommand::WithdrawFees { market } => {
+                let asset_mint = self.market(market)?.asset_mint;
+                let token_program = self.token_program(&asset_mint)?;
+                self.send(&[
+                    self.create_ata_ix(&asset_mint, &token_program),
+                    withdraw_fees(signer, asset_mint, token_program, WithdrawFeesParams { ix: market }),
+                ])?;
+            },
+            MarketCommand::Show { market } => self.show_market(market)?,
+        }
+
+        Ok(())
+    }
+
+    fn show_market(&self, market_ix: u16) -> Result<()> {
+        let market = self.market(market_ix)?;
+        let fees_vault = find_protocol_fees_vault_address(market_ix).0;
+        let fees = self.rpc.get_token_account_balance(&fees_vault)?;
+
+        println!("Market {} ({})", market.id, find_market_address(market_ix).0);
+        println!("  asset mint:            {} ({} decimals)", market.asset_mint, market.asset_decimals);
+        println!("  price feed:            {}", market.price_feed);
+        println!("  status:                {}", format_status(market.status));
+        println!("  pricing model:         {:?}, {:?} exercise", market.pricing_model, market.exercise_style);
+        println!("  contract size:         {} base units", market.contract_size);
+        println!("  strike tick:           ${}, band {}..{} bps of spot", format_usd(market.strike_tick_size), market.min_strike_moneyness_bps, market.max_strike_moneyness_bps);
+        println!("  fee:                   {} bps, keeper bounty {} bps, sell back spread {} bps", market.fee_bps, market.keeper_bounty_bps, market.sell_back_spread_bps);
+        println!("  reserve:               {}", market.reserve_supply);
+        println!("  premiums:              {}", market.premiums);
+        println!("  committed:             {}", market.committed_reserve);
+        println!("  utilization:           {}", format_ratio(utilization_bps(market.committed_reserve, market.reserve_supply), "%"));
+        println!("  LP minted:             {}", market.lp_minted);
+        println!("  LP share price:        {}", lp_share_price(market.reserve_supply, market.premiums, market.lp_minted).map_or("-".to_string(), |price| format!("{price:.9} asset per LP token")));
+        println!("  protocol fees:         {}", fees.amount);
+        println!("  vol surface (bps), updated at {}:", market.vol_last_updated);
+        for (row, tenor) in market.vol_surface_bps.iter().zip(["1H", "4H", "1D", "3D", "1W"]) {
+            println!("    {tenor:>3} {}", row.iter().map(|vol| format!("{vol:>6}")).collect::<String>());
+        }
+
+        Ok(())
+    }
+
+    fn buy(&self, args: BuyArgs) -> Result<()> {
+        let asset_mint = self.market(args.market)?.asset_mint;
+        let token_program = self.token_program(&asset_mint)?;
+        let max_spot_deviation = args.quoted_spot.zip(args.max_spot_deviation_bps)
+            .map(|(quoted_spot_price, max_deviation_bps)| SpotSlippage { quoted_spot_price, max_deviation_bps });
+        let option: OptionType = args.option.into();
+
+        let ix = match (args.strike, args.deviation) {
+            (Some(strike_price), _) => buy_at_strike(self.payer.pubkey(), asset_mint, args.price_update, token_program, BuyOptionAtStrikeParams {
+                market_ix: args.market,
+                page: args.page,
+                option,
+                strike_price,
+                expiry_setting: args.expiry,
+                quantity: args.quantity,
+                max_premium_tokens: args.max_premium,
+                max_spot_deviation,
+            }),
+            (None, Some(deviation)) => buy(self.payer.pubkey(), asset_mint, args.price_update, token_program, BuyOptionParams {
+                market_ix: args.market,
+                page: args.page,
+                option,
+                spot_deviation: deviation.into(),
+                expiry_setting: args.expiry,
+                quantity: args.quantity,
+                max_premium_tokens: args.max_premium,
+                max_spot_deviation,
+            }),
+            (None, None) => bail!("either --deviation or --strike is required"),
+        };
+
+        self.send(&[ix])?;
+        Ok(())
+    }
+
+    fn exercise(&self, market_ix: u16, option_id: u8, quantity: Option<u64>, page: u8, price_update: Pubkey) -> Result<()> {
+        let market = self.market(market_ix)?;
+        let owner = self.payer.pubkey();
+        let data = self.rpc.get_account_data(&find_user_account_address(&owner, page).0)
+            .with_context(|| format!("account page {page} not found"))?;
+        let account = decode_user_account(&data).map_err(|e| anyhow!("decoding account page {page}: {e}"))?;
+
+        let option = account.options.get(option_id as usize)
+            .filter(|option| option.is_initialized() && option.market_ix == market_ix)
+            .ok_or_else(|| anyhow!("no option {option_id} of market {market_ix} in page {page}"))?;
+
+        //European options settle at the price recorded for their expiry
+        let settlement = match market.exercise_style {
+            ExerciseStyle::American => None,
+            ExerciseStyle::European => Some(find_settlement_address(market_ix, option.expiry).0),
+        };
+
+        let params = ExerciseOptionParams { market_ix, page, option_id, quantity: quantity.unwrap_or(option.quantity) };
+        let token_program = self.token_program(&market.asset_mint)?;
+        self.send(&[
+            self.create_ata_ix(&market.asset_mint, &token_program),
+            exercise(owner, market.asset_mint, price_update, settlement, token_program, params),
+        ])?;
+        Ok(())
+    }
+
+    fn positions(&self, owner: Pubkey) -> Result<()> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs() as i64;
+        let pages: Vec<u8> = (0..=u8::MAX).collect();
+        let mut found = 0;
+        //Exercise window per market, fetched once
+        let mut exercise_windows: HashMap<u16, i64> = HashMap::new();
+
+        for chunk in pages.chunks(PAGES_PER_REQUEST) {
+            let addresses: Vec<Pubkey> = chunk.iter().map(|page| find_user_account_address(&owner, *page).0).collect();
+            let accounts = self.rpc.get_multiple_accounts(&addresses)?;
+
+            for (page, account) in chunk.iter().zip(accounts) {
+                let Some(account) = account else { continue };
+                let user_account: UserAccount = decode_user_account(&account.data)
+                    .map_err(|e| anyhow!("decoding account page {page}: {e}"))?;
+
+                for option in user_account.options.iter().filter(|option| option.is_initialized()) {
+                    found += 1;
+                    let option_type = OptionType::try_from(option.option_type).map_or("?", |option_type| match option_type {
+                        OptionType::CALL => "CALL",
+                        OptionType::PUT => "PUT",
+                    });
+                    let exercise_window = match exercise_windows.get(&option.market_ix) {
+                        Some(exercise_window) => *exercise_window,
+                        None => {
+                            let exercise_window = self.market(option.market_ix)
+                                .with_context(|| format!("reading the exercise window of page {page} slot {}", option.ix))?
+                                .exercise_window();
+                            exercise_windows.insert(option.market_ix, exercise_window);
+                            exercise_window
+                        }
+                    };
+                    let state = if option.is_lapsed(now, exercise_window) {
+                        "lapsed"
+                    } else if now >= option.expiry {
+                        "exercise window"
+                    } else {
+                        "open"
+                    };
+
+                    println!(
+                        "page {page:>3} slot {:>2}  market {:>3}  {option_type:<4} strike ${:<14} qty {:<8} expiry {} ({state})  collateral {}  premium {}",
+                        option.ix, option.market_ix, format_usd(option.strike_price), option.quantity, option.expiry,
+                        option.max_potential_payout_in_tokens, option.premium
+                    );
+                }
+            }
+        }
+
+        if found == 0 {
+            println!("No open positions for {owner}");
+        } else {
+            println!("{found} position(s). Exercise window closes {EXERCISE_INTERVAL_TOLERANCE}s after expiry, {EUROPEAN_EXERCISE_WINDOW_SECS}s on European markets");
+        }
+        Ok(())
+    }
+}
+
+//5 values are one per tenor, flat across strikes. 45 values are the full surface, row by row
+pub fn vol_surface(vols_bps: &[u32]) -> Result<[[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES]> {
+    let mut surface = [[0; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES];
+
+    match vols_bps.len() {
+        VOL_SURFACE_EXPIRIES => {
+            for (row, vol) in surface.iter_mut().zip(vols_bps) {
+                *row = [*vol; VOL_SURFACE_STRIKES];
+            }
+        },
+        len if len == VOL_SURFACE_EXPIRIES * VOL_SURFACE_STRIKES => {
+            for (row, vols) in surface.iter_mut().zip(vols_bps.chunks(VOL_SURFACE_STRIKES)) {
+                row.copy_from_slice(vols);
+            }
+        },
+        len => bail!("expected {} or {} volatilities, got {len}", VOL_SURFACE_EXPIRIES, VOL_SURFACE_EXPIRIES * VOL_SURFACE_STRIKES),
+    }
+
+    Ok(surface)
+}
+
+//Share of the LP reserve committed as collateral
+pub fn utilization_bps(committed_reserve: u64, reserve_supply: u64) -> Option<u64> {
+    (reserve_supply > 0).then(|| (committed_reserve as u128 * 10_000 / reserve_supply as u128) as u64)
+}
+
+//Asset tokens one LP token redeems for, before the uncommitted reserve cap. LP mint has the asset decimals
+pub fn lp_share_price(reserve_supply: u64, premiums: u64, lp_minted: u64) -> Option<f64> {
+    (lp_minted > 0).then(|| (reserve_supply as f64 + premiums as f64) / lp_minted as f64)
+}
+
+fn format_ratio(bps: Option<u64>, unit: &str) -> String {
+    bps.map_or("-".to_string(), |bps| format!("{}.{:02}{unit}", bps / 100, bps % 100))
+}
+
+fn format_status(status: u8) -> String {
+    if status == 0 {
+        return "active".to_string();
+    }
+
+    let paused: Vec<&str> = [
+        (MARKET_PAUSE_BUY, "buy"),
+        (MARKET_PAUSE_DEPOSIT, "deposit"),
+        (MARKET_PAUSE_WITHDRAW, "withdraw"),
+        (MARKET_PAUSE_EXERCISE, "exercise"),
+        (MARKET_PAUSE_SELL_BACK, "sell back"),
+    ]
+    .into_iter()
+    .filter(|(flag, _)| status & flag != 0)
+    .map(|(_, name)| name)
+    .collect();
+
+    format!("paused: {}", paused.join(", "))
+}
@@ -0,0 +1,12 @@
+use anyhow::Result;
+use clap::Parser;
+
+mod args;
+mod commands;
+mod tests;
+
+fn main() -> Result<()> {
+    let cli = args::Cli::parse();
+    let client = commands::Client::new(&cli.url, cli.keypair.as_deref())?;
+    client.run(cli.command)
+}
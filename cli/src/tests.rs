@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod parsing {
+    use options_program_sdk::common::ExpirySetting;
+
+    use crate::args::*;
+    use crate::commands::vol_surface;
+
+    #[test]
+    fn usd_amounts_scale_to_price_decimals() {
+        assert_eq!(parse_usd("150").unwrap(), 15_000_000_000);
+        assert_eq!(parse_usd("0.01").unwrap(), 1_000_000);
+        assert_eq!(parse_usd(".5").unwrap(), 50_000_000);
+        assert_eq!(format_usd(parse_usd("142.12345678").unwrap()), "142.12345678");
+        assert!(parse_usd("0.123456789").is_err());
+        assert!(parse_usd("").is_err());
+        assert!(parse_usd("-1").is_err());
+        assert!(parse_usd("184467440738").is_err());
+    }
+
+    #[test]
+    fn market_names_fit_the_account() {
+        assert_eq!(parse_market_name("SOL/USD").unwrap(), "SOL/USD");
+        assert!(parse_market_name(&"x".repeat(32)).is_ok());
+        assert!(parse_market_name(&"x".repeat(33)).is_err());
+        assert!(parse_market_name("").is_err());
+    }
+
+    #[test]
+    fn expiry_series_are_parsed() {
+        assert!(matches!(parse_expiry("daily:1").unwrap(), ExpirySetting::Daily { offset: 1 }));
+        assert!(matches!(parse_expiry("weekly:0").unwrap(), ExpirySetting::Weekly { offset: 0 }));
+        assert!(matches!(parse_expiry("custom:1792137600").unwrap(), ExpirySetting::Custom { expiry_stamp: 1_792_137_600 }));
+        assert!(parse_expiry("monthly:1").is_err());
+        assert!(parse_expiry("daily").is_err());
+    }
+
+    #[test]
+    fn vol_surface_from_tenors_or_full_grid() {
+        let flat = vol_surface(&[8000, 9000, 8000, 7500, 7000]).unwrap();
+        assert_eq!(flat[1], [9000; 9]);
+        assert_eq!(flat[4], [7000; 9]);
+
+        let full: Vec<u32> = (0..45).collect();
+        let surface = vol_surface(&full).unwrap();
+        assert_eq!(surface[2][3], 21);
+
+        assert!(vol_surface(&[8000; 6]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod market_stats {
+    use crate::commands::{lp_share_price, utilization_bps};
+
+    #[test]
+    fn utilization_and_share_price() {
+        assert_eq!(utilization_bps(0, 0), None);
+        assert_eq!(utilization_bps(250_000_000, 1_000_000_000), Some(2500));
+        assert_eq!(utilization_bps(1, 3), Some(3333));
+
+        assert_eq!(lp_share_price(0, 0, 0), None);
+        assert_eq!(lp_share_price(1_000_000_000, 100_000_000, 1_000_000_000), Some(1.1));
+    }
+}
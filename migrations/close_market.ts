@@ -80,6 +80,11 @@ async function detectTokenProgram(connection: Connection, mint: PublicKey): Prom
     }
   
    //---Derive PDAs---//
+   const [protocolConfigPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
+       [Buffer.from('protocol_config')],
+       program.programId
+     );
+
    const [marketPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
        [
          Buffer.from('market'),
@@ -123,6 +128,7 @@ async function detectTokenProgram(connection: Connection, mint: PublicKey): Prom
     }) 
     .accountsStrict({
         admin: admin.publicKey,
+        config: protocolConfigPDA,
         assetMint: new PublicKey(assetMint),
         adminAssetAta: adminAta,
         lpMint: lpMintPDA,
@@ -12,15 +12,16 @@ Args:
 <protocol-fees-bps> - protocol basis points  // 1bps = 0.01%
 <market-ix> - market index
 <asset-mint> - asset mint
+<strike-tick-size> - strike tick size in usd, scaled by 10^8 // 100000000 = $1
 
 Full command example:
 
-ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts <pyth-price-feed> <protocol-fees-bps> <market-ix> <asset-mint>
+ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts <pyth-price-feed> <protocol-fees-bps> <market-ix> <asset-mint> <strike-tick-size>
 */
 
-// wSOL market - ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts 0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d 50 1 So11111111111111111111111111111111111111112
+// wSOL market - ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts 0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d 50 1 So11111111111111111111111111111111111111112 100000000
 
-//JUP market - ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts 0x0a0408d619e9380abad35060f9192039ed5042fa6f82301d0e48bb52be830996 100 2 JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN
+//JUP market - ANCHOR_PROVIDER_URL=http://127.0.0.1:8899 ANCHOR_WALLET=$HOME/.config/solana/id.json npx ts-node migrations/create_market.ts 0x0a0408d619e9380abad35060f9192039ed5042fa6f82301d0e48bb52be830996 100 2 JUPyiwrYJFskUPiHa7hkeR8VUtAeFoSYbKedZNsDvCN 1000000
 
 async function detectTokenProgram(connection: Connection, mint: PublicKey): Promise<PublicKey> {
   const mintAccount = await connection.getAccountInfo(mint);
@@ -44,24 +45,31 @@ async function detectTokenProgram(connection: Connection, mint: PublicKey): Prom
     const program = anchor.workspace.OptionsProgram as Program<OptionsProgram>;
   
     const args = process.argv.slice(2);
-    if (args.length < 4) {
-      console.error("Usage: ts-node create_market.ts <price_feed_address> <fee_basis_points>");
+    if (args.length < 6) {
+      console.error("Usage: ts-node create_market.ts <price_feed_address> <fee_basis_points> <market_ix> <asset_mint> <strike_tick_size> <contract_size>");
       process.exit(1);
     }
   
-    console.log("Arguments received: ", args[0], args[1], args[2], args[3]);
+    console.log("Arguments received: ", args[0], args[1], args[2], args[3], args[4], args[5]);
     const pythFeed = args[0];
     const protocolFeeBps = Number(args[1]);
     const marketIx = Number(args[2]);
     const assetMint = args[3];
+    const strikeTickSize = args[4];
+    const contractSize = args[5];
 
-    console.log('Parsed values: ', pythFeed, protocolFeeBps, marketIx, assetMint);
+    console.log('Parsed values: ', pythFeed, protocolFeeBps, marketIx, assetMint, strikeTickSize, contractSize);
 
     const admin = provider.wallet as anchor.Wallet;
     const token_program_id = await detectTokenProgram(provider.connection, new PublicKey(assetMint));
   
    //---Derive PDAs---//
    //Could extreact common account derivation logic into utils class...too much code duplication
+   const [protocolConfigPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
+       [Buffer.from('protocol_config')],
+       program.programId
+     );
+
    const [marketPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
        [
          Buffer.from('market'),
@@ -107,11 +115,29 @@ async function detectTokenProgram(connection: Connection, mint: PublicKey): Prom
           name: '--', //todo remove later
           ix: marketIx,
           priceFeed: pythFeed,
-          hour1VolatilityBps: 8000,
-          hour4VolatilityBps: 9000,
-          day1VolatilityBps: 8000,
-          day3VolatilityBps: 7500,
-          weekVolatilityBps: 7000,
+          // [expiry][strike] - rows 1H, 4H, 1D, 3D, 1W; columns 80% .. 120% of spot. Flat across strikes
+          volSurfaceBps: [8000, 9000, 8000, 7500, 7000].map(atmVol => Array(9).fill(atmVol)),
+          maxVolAgeSecs: new anchor.BN(3600),
+          minVolatilityBps: 1000,
+          maxVolatilityBps: 30000,
+          maxVolChangeBps: 5000,
+          keeperBountyBps: new anchor.BN(50),
+          pricingModel: { blackScholes: {} },
+          // Increased max price age, because the cloned pyth account has a stale price
+          maxPriceAgeBuySecs: new anchor.BN(3600),
+          maxPriceAgeExerciseSecs: new anchor.BN(90),
+          maxPriceConfBps: new anchor.BN(100),
+          exerciseUseEma: false,
+          strikeTickSize: new anchor.BN(strikeTickSize),
+          minStrikeMoneynessBps: 8000,
+          maxStrikeMoneynessBps: 12000,
+          // Daily, weekly and custom expiries. Series expire at 08:00 UTC
+          expirySeries: 0b111,
+          expiryHourUtc: 8,
+          minTimeToExpirySecs: new anchor.BN(900),
+          exerciseStyle: { american: {} },
+          sellBackSpreadBps: new anchor.BN(200),
+          contractSize: new anchor.BN(contractSize),
         }) 
           .accountsStrict({
             market: marketPDA,
@@ -121,6 +147,7 @@ async function detectTokenProgram(connection: Connection, mint: PublicKey): Prom
             assetMint: new PublicKey(assetMint),
             tokenProgram: token_program_id,
             signer: admin.publicKey,
+            config: protocolConfigPDA,
             systemProgram: SYSTEM_PROGRAM_ID
         })
         .signers([admin.payer])
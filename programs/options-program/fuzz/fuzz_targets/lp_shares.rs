@@ -0,0 +1,89 @@
+#![no_main]
+
+//Random sequences of deposits, premium accruals, payouts and withdrawals against a market.
+//Same invariants as the lp_share_invariants proptest, libFuzzer also reports any panic.
+
+use anchor_lang::{AnchorDeserialize, Space};
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use options_program::state::market::Market;
+
+const LPS: usize = 3;
+
+#[derive(Arbitrary, Debug)]
+enum Op {
+    Deposit { lp: u8, amount: u64 },
+    Withdraw { lp: u8, lp_tokens: u64 },
+    Buy { premium: u64, collateral: u64 },
+    Exercise { option: u8, payout_bps: u16 },
+}
+
+fn tvl(market: &Market) -> u128 {
+    market.reserve_supply as u128 + market.premiums as u128
+}
+
+fn share_price_held(before: &Market, after: &Market) -> bool {
+    before.lp_minted == 0 || after.lp_minted == 0
+        || tvl(after) * before.lp_minted as u128 >= tvl(before) * after.lp_minted as u128
+}
+
+fuzz_target!(|ops: Vec<Op>| {
+    //Only the pool balances matter, the rest of the market stays zeroed
+    let mut pool = Market::deserialize(&mut &[0u8; Market::INIT_SPACE][..]).unwrap();
+    let mut lp_balances = [0u64; LPS];
+    let mut options: Vec<u64> = Vec::new();
+
+    for op in ops {
+        //A failed instruction reverts, so ops run on a copy that is kept only on success
+        let before = pool.clone();
+        let mut market = pool.clone();
+
+        match op {
+            Op::Deposit { lp, amount } => {
+                let mut round_trip = market.clone();
+                if let Ok(lp_tokens) = round_trip.add_liquidity(amount, 1) {
+                    if let Ok((withdrawn, _)) = round_trip.remove_liquidity(lp_tokens, 0) {
+                        assert!(withdrawn <= amount, "round trip of {} returned {}", amount, withdrawn);
+                    }
+                }
+
+                if let Ok(lp_tokens) = market.add_liquidity(amount, 1) {
+                    assert!(share_price_held(&before, &market), "deposit diluted LPs");
+                    lp_balances[lp as usize % LPS] += lp_tokens;
+                    pool = market;
+                }
+            }
+            Op::Withdraw { lp, lp_tokens } => {
+                let lp = lp as usize % LPS;
+                let requested = lp_tokens % (lp_balances[lp] + 1);
+                if let Ok((withdrawn, burned)) = market.remove_liquidity(requested, 0) {
+                    assert!(burned <= requested);
+                    assert!(withdrawn as u128 <= tvl(&before) - before.committed_reserve as u128, "committed reserve withdrawn");
+                    assert!(share_price_held(&before, &market), "withdrawal diluted LPs");
+                    lp_balances[lp] -= burned;
+                    pool = market;
+                }
+            }
+            Op::Buy { premium, collateral } => {
+                if market.reserve_supply - market.committed_reserve > collateral && market.accrue_premium(premium, collateral).is_ok() {
+                    options.push(collateral);
+                    pool = market;
+                }
+            }
+            Op::Exercise { option, payout_bps } => {
+                if !options.is_empty() {
+                    let option = option as usize % options.len();
+                    let payout = (options[option] as u128 * payout_bps.min(10_000) as u128 / 10_000) as u64;
+                    if market.pay_out(payout).and_then(|_| market.release_collateral(options[option])).is_ok() {
+                        options.swap_remove(option);
+                        pool = market;
+                    }
+                }
+            }
+        }
+
+        assert!(pool.reserve_supply >= pool.committed_reserve, "committed reserve not backed");
+        assert_eq!(pool.committed_reserve, options.iter().sum::<u64>());
+        assert_eq!(pool.lp_minted, lp_balances.iter().sum::<u64>());
+    }
+});
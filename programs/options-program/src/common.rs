@@ -1,6 +1,6 @@
 use anchor_lang::prelude::*;
 
-use crate::errors::CustomError;
+use crate::{constants::{BASIS_POINTS_DENOMINATOR, MAX_EXPIRY_DAYS, PRICE_DECIMALS, SECONDS_IN_DAY}, errors::CustomError};
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
 pub enum OptionType {
@@ -34,6 +34,37 @@ impl TryFrom<u8> for OptionType {
     }
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy, InitSpace)]
+pub enum PricingModel {
+    Legacy,         // Intrinsic value + spot * vol * sqrt(t) with OTM discount
+    BlackScholes
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy, InitSpace)]
+pub enum ExerciseStyle {
+    American,       // Exercise any time up to expiry + tolerance at the current oracle price
+    European        // Exercise after expiry only, at the settlement price recorded for the expiry
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
+pub enum Role {
+    VolKeeper,
+    FeeCollector,
+    MarketManager,
+    Pauser
+}
+
+impl Role {
+    pub fn mask(&self) -> u8 {
+        match self {
+            Role::VolKeeper => 1 << 0,
+            Role::FeeCollector => 1 << 1,
+            Role::MarketManager => 1 << 2,
+            Role::Pauser => 1 << 3,
+        }
+    }
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
 pub enum SpotDeviation {
     N20,
@@ -48,8 +79,9 @@ pub enum SpotDeviation {
 }
 
 impl SpotDeviation {
-    pub fn convert_to_strike(&self, spot_price: u128) -> Result<u128> {
-        let deviation = match &self {
+    //Strike as percent of spot
+    pub fn percent(&self) -> u128 {
+        match &self {
             SpotDeviation::N20 => { 80 },
             SpotDeviation::N15 => { 85 },
             SpotDeviation::N10 => { 90 },
@@ -59,24 +91,24 @@ impl SpotDeviation {
             SpotDeviation::P10 => { 110 },
             SpotDeviation::P15 => { 115 },
             SpotDeviation::P20 => { 120 },
-        };
+        }
+    }
 
-        //Imperfect, but good enough to support JUP tokens for demo...
-        let step = if spot_price < 100_000_000 {
-            1_000_000
-        } else {
-            100_000_000
-        };
+    pub fn convert_to_strike(&self, spot_price: u128, tick_size: u128) -> Result<u128> {
+        require!(tick_size > 0, CustomError::InvalidStrikePrice);
+        let deviation = self.percent();
 
         let adjusted = spot_price
-                    .checked_mul(deviation).unwrap()
-                    .checked_div(100).unwrap();
+                    .checked_mul(deviation).ok_or(CustomError::Overflow)?
+                    .checked_div(100).ok_or(CustomError::Overflow)?;
 
-        let round_up = (adjusted + step / 2) / step;
-        Ok((round_up * step) as u128)
+        //Round to the nearest strike tick
+        let round_up = (adjusted + tick_size / 2) / tick_size;
+        Ok(round_up * tick_size)
     }
 }
 
+//Tenors of the market volatility surface
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
 pub enum Expiry {
     HOUR1,
@@ -92,6 +124,17 @@ pub enum ExpiryError {
 }
 
 impl Expiry {
+    //Row of the expiry bucket in the market volatility surface
+    pub fn index(&self) -> usize {
+        match self {
+            Expiry::HOUR1 => 0,
+            Expiry::HOUR4 => 1,
+            Expiry::DAY1 => 2,
+            Expiry::DAY3 => 3,
+            Expiry::WEEK => 4,
+        }
+    }
+
     pub fn to_seconds(&self) -> std::result::Result<u64, ExpiryError> {
         match self {
             Expiry::HOUR1 => Ok(60 * 60),
@@ -118,15 +161,120 @@ impl TryFrom<u8> for Expiry {
     }   
 }
 
-pub fn calc_time_distance(stamp_now: i64, expiry_stamp: i64) -> Result<f64> {
+//Strike of a quote, as `buy` (deviation from spot) or `buy_at_strike` (explicit strike) would pick it
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
+pub enum StrikeSetting {
+    SpotDeviation { deviation: SpotDeviation },
+    Price { strike_price: u64 }     // usd scaled by 10^8, must be a multiple of the market strike tick size
+}
+
+//Expiry chosen by the buyer. Series expiries are shared by every option bought into them
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
+pub enum ExpirySetting {
+    Daily { offset: u8 },           // Next daily expiry + offset days
+    Weekly { offset: u8 },          // Next Friday expiry + offset weeks
+    Custom { expiry_stamp: i64 }
+}
+
+const FRIDAY: i64 = 5; //Day of week, Sunday = 0
+
+impl ExpirySetting {
+    //Series expiries fall on expiry_hour_utc. The next one is the first at least min_time_to_expiry away
+    pub fn to_timestamp(self, stamp_now: i64, expiry_hour_utc: u8, min_time_to_expiry: i64) -> Result<i64> {
+        let earliest = stamp_now.checked_add(min_time_to_expiry).ok_or(CustomError::Overflow)?;
+        let earliest_day = earliest.div_euclid(SECONDS_IN_DAY);
+        let first_daily = earliest_day * SECONDS_IN_DAY + expiry_hour_utc as i64 * 3600;
+
+        let expiry_stamp = match self {
+            ExpirySetting::Daily { offset } => {
+                let next = if first_daily < earliest { first_daily + SECONDS_IN_DAY } else { first_daily };
+                next + offset as i64 * SECONDS_IN_DAY
+            },
+            ExpirySetting::Weekly { offset } => {
+                //1970-01-01 was a Thursday
+                let weekday = (earliest_day + 4).rem_euclid(7);
+                let friday = first_daily + (FRIDAY - weekday).rem_euclid(7) * SECONDS_IN_DAY;
+                let next = if friday < earliest { friday + 7 * SECONDS_IN_DAY } else { friday };
+                next + offset as i64 * 7 * SECONDS_IN_DAY
+            },
+            ExpirySetting::Custom { expiry_stamp } => {
+                require!(expiry_stamp >= earliest, CustomError::InvalidExpiry);
+                expiry_stamp
+            }
+        };
+
+        calc_time_distance(stamp_now, expiry_stamp)?;
+        Ok(expiry_stamp)
+    }
+}
+
+//Buyer's guard against the oracle moving between quote and inclusion. The strike of spot deviation buys follows spot,
+//so this bounds the strike drift too
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug, Copy)]
+pub struct SpotSlippage {
+    pub quoted_spot_price: u64,     // usd scaled by 10^8, spot the buyer was quoted at
+    pub max_deviation_bps: u64      // Max move of the oracle price off the quoted spot
+}
+
+impl SpotSlippage {
+    pub fn check(&self, spot_price: u128) -> Result<()> {
+        let quoted = self.quoted_spot_price as u128;
+        require!(quoted > 0, CustomError::InvalidSpotPrice);
+
+        //deviation / quoted <= max_deviation_bps / 10000, without rounding
+        let deviation = spot_price.abs_diff(quoted)
+            .checked_mul(BASIS_POINTS_DENOMINATOR as u128).ok_or(CustomError::Overflow)?;
+        let allowed = quoted
+            .checked_mul(self.max_deviation_bps as u128).ok_or(CustomError::Overflow)?;
+        require!(deviation <= allowed, CustomError::SlippageExceeded);
+
+        Ok(())
+    }
+}
+
+//Edge of the oracle confidence interval that is worse for the option buyer (higher premium and collateral)
+pub fn buyer_spot_price(spot_price: u128, conf: u128, option_type: &OptionType) -> Result<u128> {
+    match option_type {
+        OptionType::CALL => spot_price.checked_add(conf).ok_or(CustomError::Overflow.into()),
+        OptionType::PUT => {
+            require!(spot_price > conf, CustomError::InvalidSpotPrice);
+            Ok(spot_price - conf)
+        }
+    }
+}
+
+//Edge of the oracle confidence interval that is worse for a holder selling the option back (lower value)
+pub fn seller_spot_price(spot_price: u128, conf: u128, option_type: &OptionType) -> Result<u128> {
+    match option_type {
+        OptionType::CALL => {
+            require!(spot_price > conf, CustomError::InvalidSpotPrice);
+            Ok(spot_price - conf)
+        },
+        OptionType::PUT => spot_price.checked_add(conf).ok_or(CustomError::Overflow.into()),
+    }
+}
+
+//Rescales an oracle value with the given exponent to PRICE_DECIMALS fixed-point
+pub fn normalize_price(value: u64, exponent: i32) -> Result<u64> {
+    let shift = PRICE_DECIMALS + exponent;
+    require!(shift.abs() <= 18, CustomError::InvalidPriceFeed);
+
+    let factor = 10_u128.pow(shift.unsigned_abs());
+    let normalized = if shift >= 0 {
+        (value as u128).checked_mul(factor).ok_or(CustomError::Overflow)?
+    } else {
+        (value as u128) / factor
+    };
+
+    u64::try_from(normalized).map_err(|_| CustomError::Overflow.into())
+}
+
+//Seconds until expiry, which must be in the future and at most MAX_EXPIRY_DAYS away
+pub fn calc_time_distance(stamp_now: i64, expiry_stamp: i64) -> Result<u64> {
     let time_distance = expiry_stamp - stamp_now;
 
-    let seconds_in_day: i64 = 86400;
     require!(time_distance > 0, CustomError::InvalidExpiry);
-    require!(time_distance / seconds_in_day <= 30, CustomError::InvalidExpiry);
-
-    let seconds_per_year: f64 = 365.25 * 24.0 * 60.0 * 60.0;
-    let time_to_expire_in_years = time_distance as f64 / seconds_per_year;
+    require!(time_distance <= MAX_EXPIRY_DAYS * SECONDS_IN_DAY, CustomError::InvalidExpiry);
 
-    Ok(time_to_expire_in_years)
+    Ok(time_distance as u64)
 }
\ No newline at end of file
@@ -1,9 +1,17 @@
-pub const ADMIN_KEY: &str = "FARXLJJbSwZyELTe8TXihES7o26B2d5NKkvCkETP7Gnz"; 
-
 pub const BASIS_POINTS_DENOMINATOR: u64 = 10_000;
 pub const EXERCISE_INTERVAL_TOLERANCE: i64 = 300; //5 mins in seconds
+pub const EUROPEAN_EXERCISE_WINDOW_SECS: i64 = 86_400; //Settlement price is fixed, so European options can be exercised for longer
+pub const SETTLEMENT_MAX_PUBLISH_DELAY_SECS: i64 = 10; //Settlement prices must be published within this long after expiry
+pub const MAX_KEEPER_BOUNTY_BPS: u64 = 100; //1% of the payout
+pub const MIN_VOLATILITY_BPS: u32 = 100; //1%
+pub const MAX_VOLATILITY_BPS: u32 = 50_000; //500%
+pub const MAX_PRICE_AGE_SECS: u64 = 3_600; //Upper bound of the per-market oracle price age limits
 
 pub const SECONDS_IN_YEAR: u128 = 31_536_000; 
+pub const SECONDS_IN_DAY: i64 = 86_400;
+pub const MAX_EXPIRY_DAYS: i64 = 30; //Options can't be bought further out than this
+
+pub const PRICE_DECIMALS: i32 = 8; //All usd prices are normalized to 10^8 regardless of the oracle exponent
 
 //pub const SOL_USD_FEED: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
 //account - 7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE
@@ -48,4 +48,20 @@ pub enum CustomError {
     VolatilityStaled,
     #[msg("InvalidSpotPrice")]
     InvalidSpotPrice,
+    #[msg("RoleLimitExceeded")]
+    RoleLimitExceeded,
+    #[msg("Operation is paused for this market")]
+    MarketPaused,
+    #[msg("VolatilityChangeTooLarge")]
+    VolatilityChangeTooLarge,
+    #[msg("PriceConfidenceTooWide")]
+    PriceConfidenceTooWide,
+    #[msg("Strike is outside of the market moneyness band")]
+    StrikeOutOfRange,
+    #[msg("Settlement price for the expiry is not recorded")]
+    SettlementNotRecorded,
+    #[msg("Account has open positions")]
+    AccountHasOpenPositions,
+    #[msg("Market still holds fees, collateral or LP deposits")]
+    MarketNotEmpty,
 }
\ No newline at end of file
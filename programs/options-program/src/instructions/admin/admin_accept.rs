@@ -0,0 +1,32 @@
+use anchor_lang::prelude::*;
+use crate::errors::*;
+use crate::state::protocol_config::*;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        constraint = signer.key() == config.pending_admin @ CustomError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+impl AcceptAdmin<'_> {
+    pub fn handle(ctx: Context<AcceptAdmin>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let previous_admin = config.admin;
+
+        config.admin = config.pending_admin;
+        config.pending_admin = Pubkey::default();
+
+        msg!("Admin handover accepted. Previous admin: {}, new admin: {}", previous_admin, config.admin);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::errors::*;
+use crate::state::protocol_config::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ProposeAdminParams {
+    pub new_admin: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    #[account(
+        constraint = signer.key() == config.admin @ CustomError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+impl ProposeAdmin<'_> {
+    pub fn handle(ctx: Context<ProposeAdmin>, params: ProposeAdminParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        //Proposed admin has to accept, so a typo in the key can't brick the protocol
+        config.pending_admin = params.new_admin;
+
+        msg!("Admin handover proposed. Current admin: {}, pending admin: {}", config.admin, config.pending_admin);
+
+        Ok(())
+    }
+}
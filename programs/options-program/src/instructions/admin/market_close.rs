@@ -1,9 +1,9 @@
-use std::str::FromStr;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked, CloseAccount };
+use crate::common::Role;
 use crate::errors::*;
 use crate::state::market::*;
-use crate::constants::ADMIN_KEY;
+use crate::state::protocol_config::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CloseMarketParams {
@@ -15,10 +15,16 @@ pub struct CloseMarketParams {
 pub struct CloseMarket<'info> {
     #[account(
         mut,
-        constraint = admin.key() == Pubkey::from_str(ADMIN_KEY).unwrap() @ CustomError::Unauthorized
+        constraint = config.has_role(&admin.key(), Role::MarketManager) @ CustomError::Unauthorized
     )]
     pub admin: Signer<'info>,
 
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
     #[account()]
     pub asset_mint: InterfaceAccount<'info, Mint>,
 
@@ -86,8 +92,13 @@ pub struct CloseMarket<'info> {
 
 impl CloseMarket<'_> {
     pub fn handle(ctx: Context<CloseMarket>, params: CloseMarketParams) -> Result<()> {
+        let market = &ctx.accounts.market;
         let market_vault = &mut ctx.accounts.market_vault;
         let fees_vault = &mut ctx.accounts.protocol_fees_vault;
+
+        //Fees leave through withdraw_fees (FeeCollector) and LP funds through market_withdraw, only dust is swept here
+        require!(fees_vault.amount == 0, CustomError::MarketNotEmpty);
+        require!(market.committed_reserve == 0 && market.lp_minted == 0 && ctx.accounts.lp_mint.supply == 0, CustomError::MarketNotEmpty);
         let market_ix_bytes = params.ix.to_le_bytes();
         
         let vault_signer_seeds: &[&[&[u8]]] = &[&[
@@ -125,19 +136,6 @@ impl CloseMarket<'_> {
             market_ix_bytes.as_ref(),
             &[ctx.bumps.protocol_fees_vault]]];
 
-        if fees_vault.amount > 0 {
-            token_interface::transfer_checked(
-                CpiContext::new(ctx.accounts.token_program.to_account_info(),
-                    TransferChecked {
-                    from: fees_vault.to_account_info(),
-                    to: ctx.accounts.admin_asset_ata.to_account_info(),
-                    authority: fees_vault.to_account_info(),
-                    mint: ctx.accounts.asset_mint.to_account_info()
-                }).with_signer(fees_signer_seeds),
-                fees_vault.amount,
-                ctx.accounts.asset_mint.decimals)?;
-        }
-
         //Close fees vault
         token_interface::close_account(CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
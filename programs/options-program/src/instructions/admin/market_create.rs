@@ -1,9 +1,10 @@
-use std::str::FromStr;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{ TokenInterface, Mint, TokenAccount };
+use crate::common::{ExerciseStyle, PricingModel, Role};
 use crate::errors::*;
 use crate::state::market::*;
-use crate::constants::ADMIN_KEY;
+use crate::constants::{BASIS_POINTS_DENOMINATOR, MAX_KEEPER_BOUNTY_BPS, MAX_PRICE_AGE_SECS, MAX_VOLATILITY_BPS, MIN_VOLATILITY_BPS, SECONDS_IN_DAY};
+use crate::state::protocol_config::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct CreateMarketParams {
@@ -11,11 +12,26 @@ pub struct CreateMarketParams {
     pub name: String, //TODO not used, remove later
     pub ix: u16, 
     pub price_feed: String, 
-    pub hour1_volatility_bps: u32,
-    pub hour4_volatility_bps: u32,
-    pub day1_volatility_bps: u32,
-    pub day3_volatility_bps: u32,
-    pub week_volatility_bps: u32
+    pub vol_surface_bps: [[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES],
+    pub max_vol_age_secs: u64,
+    pub min_volatility_bps: u32,
+    pub max_volatility_bps: u32,
+    pub max_vol_change_bps: u32,
+    pub keeper_bounty_bps: u64,
+    pub pricing_model: PricingModel,
+    pub max_price_age_buy_secs: u64,
+    pub max_price_age_exercise_secs: u64,
+    pub max_price_conf_bps: u64,
+    pub exercise_use_ema: bool,
+    pub strike_tick_size: u64,
+    pub min_strike_moneyness_bps: u32,
+    pub max_strike_moneyness_bps: u32,
+    pub expiry_series: u8,
+    pub expiry_hour_utc: u8,
+    pub min_time_to_expiry_secs: u64,
+    pub exercise_style: ExerciseStyle,
+    pub sell_back_spread_bps: u64,
+    pub contract_size: u64              // Asset base units per unit of option quantity
 }
 
 #[derive(Accounts)]
@@ -23,10 +39,16 @@ pub struct CreateMarketParams {
 pub struct CreateMarket<'info> {
     #[account(
         mut,
-        constraint = signer.key() == Pubkey::from_str(ADMIN_KEY).unwrap() @ CustomError::Unauthorized
+        constraint = config.has_role(&signer.key(), Role::MarketManager) @ CustomError::Unauthorized
     )]
     pub signer: Signer<'info>,
 
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
     #[account()]
     pub asset_mint: InterfaceAccount<'info, Mint>,
 
@@ -94,18 +116,66 @@ impl CreateMarket<'_> {
         let asset_mint = &mut ctx.accounts.asset_mint;
         let market_acc_info = market.to_account_info();
 
+        require!(params.keeper_bounty_bps <= MAX_KEEPER_BOUNTY_BPS, CustomError::InvalidAmount);
+        require!(params.sell_back_spread_bps <= BASIS_POINTS_DENOMINATOR, CustomError::InvalidAmount);
+        require!(params.max_vol_age_secs > 0, CustomError::InvalidAmount);
+        require!(
+            0 < params.max_price_age_buy_secs && params.max_price_age_buy_secs <= MAX_PRICE_AGE_SECS
+            && 0 < params.max_price_age_exercise_secs && params.max_price_age_exercise_secs <= MAX_PRICE_AGE_SECS,
+            CustomError::InvalidAmount
+        );
+        require!(0 < params.max_price_conf_bps && params.max_price_conf_bps <= BASIS_POINTS_DENOMINATOR, CustomError::InvalidAmount);
+        require!(params.contract_size > 0, CustomError::InvalidQuantity);
+        require!(params.strike_tick_size > 0, CustomError::InvalidStrikePrice);
+        require!(
+            0 < params.min_strike_moneyness_bps
+            && params.min_strike_moneyness_bps <= BASIS_POINTS_DENOMINATOR as u32
+            && BASIS_POINTS_DENOMINATOR as u32 <= params.max_strike_moneyness_bps,
+            CustomError::InvalidStrikePrice
+        );
+        require!(
+            params.expiry_series != 0
+            && params.expiry_series & !EXPIRY_SERIES_ALL == 0
+            && params.expiry_hour_utc < 24
+            && (params.min_time_to_expiry_secs as i64) < SECONDS_IN_DAY,
+            CustomError::InvalidExpiry
+        );
+        require!(
+            MIN_VOLATILITY_BPS <= params.min_volatility_bps 
+            && params.min_volatility_bps <= params.max_volatility_bps
+            && params.max_volatility_bps <= MAX_VOLATILITY_BPS,
+            CustomError::InvalidVolatility
+        );
+
         market.id = params.ix;
         market.name = params.name;
         market.fee_bps = params.fee;
         market.bump = ctx.bumps.market;
         market.price_feed = params.price_feed;
         market.asset_decimals = asset_mint.decimals;
-        market.hour1_volatility_bps = params.hour1_volatility_bps;
-        market.hour4_volatility_bps = params.hour4_volatility_bps;
-        market.day1_volatility_bps = params.day1_volatility_bps;
-        market.day3_volatility_bps = params.day3_volatility_bps;
-        market.week_volatility_bps = params.week_volatility_bps;
-        market.vol_last_updated = 0;
+        market.min_volatility_bps = params.min_volatility_bps;
+        market.max_volatility_bps = params.max_volatility_bps;
+        market.max_vol_change_bps = params.max_vol_change_bps;
+        market.max_vol_age_secs = params.max_vol_age_secs;
+        market.validate_vol_surface(&params.vol_surface_bps)?;
+        market.vol_surface_bps = params.vol_surface_bps;
+        market.vol_last_updated = Clock::get()?.unix_timestamp;
+        market.status = 0;
+        market.keeper_bounty_bps = params.keeper_bounty_bps;
+        market.pricing_model = params.pricing_model;
+        market.max_price_age_buy_secs = params.max_price_age_buy_secs;
+        market.max_price_age_exercise_secs = params.max_price_age_exercise_secs;
+        market.max_price_conf_bps = params.max_price_conf_bps;
+        market.exercise_use_ema = params.exercise_use_ema;
+        market.strike_tick_size = params.strike_tick_size;
+        market.min_strike_moneyness_bps = params.min_strike_moneyness_bps;
+        market.max_strike_moneyness_bps = params.max_strike_moneyness_bps;
+        market.expiry_series = params.expiry_series;
+        market.expiry_hour_utc = params.expiry_hour_utc;
+        market.min_time_to_expiry_secs = params.min_time_to_expiry_secs;
+        market.exercise_style = params.exercise_style;
+        market.sell_back_spread_bps = params.sell_back_spread_bps;
+        market.contract_size = params.contract_size;
         market.asset_mint = asset_mint.key();
 
         msg!("Market seeds: {:?} {:?}", MARKET_SEED.as_bytes(), params.ix.to_le_bytes());
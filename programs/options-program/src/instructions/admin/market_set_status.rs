@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::common::Role;
+use crate::errors::*;
+use crate::state::market::*;
+use crate::state::protocol_config::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetMarketStatusParams {
+    pub ix: u16,
+    pub status: u8,     // Bitfield of MARKET_PAUSE_* flags, 0 = fully active
+}
+
+#[derive(Accounts)]
+#[instruction(params: SetMarketStatusParams)]
+pub struct SetMarketStatus<'info> {
+    #[account(
+        constraint = config.has_role(&signer.key(), Role::Pauser) @ CustomError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.ix.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub market: Account<'info, Market>,
+}
+
+impl SetMarketStatus<'_> {
+    pub fn handle(ctx: Context<SetMarketStatus>, params: SetMarketStatusParams) -> Result<()> {
+        require!(params.status & !MARKET_PAUSE_ALL == 0, CustomError::InvalidState);
+
+        let market = &mut ctx.accounts.market;
+        let status_before = market.status;
+        market.status = params.status;
+
+        msg!("Market {} status updated: {:#06b} -> {:#06b}", market.id, status_before, market.status);
+
+        Ok(())
+    }
+}
@@ -1,17 +1,13 @@
-use std::str::FromStr;
 use anchor_lang::prelude::*;
+use crate::common::{Expiry, Role, SpotDeviation};
 use crate::errors::*;
 use crate::state::market::*;
-use crate::constants::ADMIN_KEY;
+use crate::state::protocol_config::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct UpdateMarketVolParams {
     pub ix: u16, 
-    pub hour1_volatility_bps: u32,
-    pub hour4_volatility_bps: u32,
-    pub day1_volatility_bps: u32,
-    pub day3_volatility_bps: u32,
-    pub week_volatility_bps: u32
+    pub vol_surface_bps: [[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES],
 }
 
 #[derive(Accounts)]
@@ -19,10 +15,16 @@ pub struct UpdateMarketVolParams {
 pub struct UpdateMarketVol<'info> {
     #[account(
         mut,
-        constraint = signer.key() == Pubkey::from_str(ADMIN_KEY).unwrap() @ CustomError::Unauthorized
+        constraint = config.has_role(&signer.key(), Role::VolKeeper) @ CustomError::Unauthorized
     )]
     pub signer: Signer<'info>,
 
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
     #[account(
         mut,
         seeds = [
@@ -40,22 +42,20 @@ impl UpdateMarketVol<'_> {
     pub fn handle(ctx: Context<UpdateMarketVol>, params: UpdateMarketVolParams) -> Result<()> {
         let market = &mut ctx.accounts.market;
 
-        market.hour1_volatility_bps = params.hour1_volatility_bps;
-        market.hour4_volatility_bps = params.hour4_volatility_bps;
-        market.day1_volatility_bps = params.day1_volatility_bps;
-        market.day3_volatility_bps = params.day3_volatility_bps;
-        market.week_volatility_bps = params.week_volatility_bps;
+        market.validate_vol_surface(&params.vol_surface_bps)?;
+        market.validate_vol_change(&params.vol_surface_bps)?;
+        market.vol_surface_bps = params.vol_surface_bps;
 
         let clock = Clock::get()?;
         market.vol_last_updated = clock.unix_timestamp;
 
-        msg!("Market {} updated. Vol data: 1H: {}, 4H: {}, 1D: {}, 3D: {}, 1W: {}", 
+        msg!("Market {} updated. ATM vol data: 1H: {}, 4H: {}, 1D: {}, 3D: {}, 1W: {}", 
             market.id, 
-            params.hour1_volatility_bps, 
-            params.hour4_volatility_bps, 
-            params.day1_volatility_bps, 
-            params.day3_volatility_bps, 
-            params.week_volatility_bps);
+            params.vol_surface_bps[Expiry::HOUR1.index()][SpotDeviation::P0 as usize], 
+            params.vol_surface_bps[Expiry::HOUR4.index()][SpotDeviation::P0 as usize], 
+            params.vol_surface_bps[Expiry::DAY1.index()][SpotDeviation::P0 as usize], 
+            params.vol_surface_bps[Expiry::DAY3.index()][SpotDeviation::P0 as usize], 
+            params.vol_surface_bps[Expiry::WEEK.index()][SpotDeviation::P0 as usize]);
 
         Ok(())
     }
@@ -1,4 +1,10 @@
+pub mod protocol_initialize;
+pub mod admin_propose;
+pub mod admin_accept;
+pub mod role_grant;
+pub mod role_revoke;
 pub mod market_create;
 pub mod market_close;
 pub mod market_update_vol;
-pub mod withdraw_fees;
\ No newline at end of file
+pub mod market_set_status;
+pub mod withdraw_fees;
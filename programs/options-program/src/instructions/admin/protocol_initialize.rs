@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+use crate::errors::*;
+use crate::program::OptionsProgram;
+use crate::state::protocol_config::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct InitializeProtocolParams {
+    pub admin: Pubkey,
+}
+
+#[derive(Accounts)]
+pub struct InitializeProtocol<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump,
+        space = 8 + ProtocolConfig::INIT_SPACE
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
+    // Only the program upgrade authority can bootstrap the protocol config
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()) @ CustomError::Unauthorized)]
+    pub program: Program<'info, OptionsProgram>,
+
+    #[account(constraint = program_data.upgrade_authority_address == Some(signer.key()) @ CustomError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+
+    pub system_program: Program<'info, System>
+}
+
+impl InitializeProtocol<'_> {
+    pub fn handle(ctx: Context<InitializeProtocol>, params: InitializeProtocolParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.admin = params.admin;
+        config.pending_admin = Pubkey::default();
+        config.bump = ctx.bumps.config;
+
+        msg!("Protocol initialized. Admin: {}", config.admin);
+
+        Ok(())
+    }
+}
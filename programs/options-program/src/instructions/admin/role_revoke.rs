@@ -0,0 +1,37 @@
+use anchor_lang::prelude::*;
+use crate::common::Role;
+use crate::errors::*;
+use crate::state::protocol_config::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RevokeRoleParams {
+    pub authority: Pubkey,
+    pub role: Role,
+}
+
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    #[account(
+        constraint = signer.key() == config.admin @ CustomError::Unauthorized
+    )]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+}
+
+impl RevokeRole<'_> {
+    pub fn handle(ctx: Context<RevokeRole>, params: RevokeRoleParams) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+
+        config.revoke_role(&params.authority, params.role);
+
+        msg!("Role {:?} revoked from {}", params.role, params.authority);
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
-use std::str::FromStr;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{ self, * };
+use crate::common::Role;
 use crate::errors::*;
 use crate::state::market::*;
-use crate::constants::ADMIN_KEY;
+use crate::state::protocol_config::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct WithdrawFeesParams {
@@ -15,10 +15,16 @@ pub struct WithdrawFeesParams {
 pub struct WithdrawFees<'info> {
     #[account(
         mut,
-        constraint = admin.key() == Pubkey::from_str(ADMIN_KEY).unwrap() @ CustomError::Unauthorized
+        constraint = config.has_role(&admin.key(), Role::FeeCollector) @ CustomError::Unauthorized
     )]
     pub admin: Signer<'info>,
 
+    #[account(
+        seeds = [PROTOCOL_CONFIG_SEED.as_bytes()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, ProtocolConfig>,
+
     #[account(
         mut,
         token::mint = asset_mint,
@@ -30,6 +36,7 @@ pub struct WithdrawFees<'info> {
     pub asset_mint: InterfaceAccount<'info, Mint>,
 
     #[account(
+        mut,
         token::mint = asset_mint,
         token::authority = protocol_fees_vault,
         token::token_program = token_program,
@@ -47,8 +54,7 @@ pub struct WithdrawFees<'info> {
 
 impl WithdrawFees<'_> {
     pub fn handle(ctx: Context<WithdrawFees>, params: WithdrawFeesParams) -> Result<()> {
-        let fees_vault = &mut ctx.accounts.protocol_fees_vault;
-        let amount = fees_vault.amount;
+        let amount = ctx.accounts.protocol_fees_vault.amount;
         
         let market_ix_bytes = params.ix.to_le_bytes();
         
@@ -64,7 +70,7 @@ impl WithdrawFees<'_> {
             TransferChecked {
                 from: ctx.accounts.protocol_fees_vault.to_account_info(),
                 to: ctx.accounts.admin_token_acc.to_account_info(),
-                authority: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.protocol_fees_vault.to_account_info(),
                 mint: ctx.accounts.asset_mint.to_account_info()
             }).with_signer(signer_seeds),
             amount,
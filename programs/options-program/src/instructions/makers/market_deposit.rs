@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::math::lp_shares::calc_lp_shares;
+use crate::errors::CustomError;
 use crate::state::market::*;
 use crate::state::event::*;
 use anchor_spl::associated_token::AssociatedToken;
@@ -78,15 +78,11 @@ impl MarketDeposit<'_> {
     pub fn handle(ctx: Context<MarketDeposit>, amount: u64, min_amount_out: u64, ix: u16) -> Result<()> {
         //Calc lp tokens(share) to mint
         let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_DEPOSIT), CustomError::MarketPaused);
+
         let lp_tokens_before = market.lp_minted;
         let market_reserve_before = market.reserve_supply;
-        let lp_tokens_to_mint = calc_lp_shares(amount, min_amount_out, market)?;
-
-        //Update market 
-        market.lp_minted = market.lp_minted
-            .checked_add(lp_tokens_to_mint).unwrap();
-        market.reserve_supply = market.reserve_supply
-            .checked_add(amount).unwrap();
+        let lp_tokens_to_mint = market.add_liquidity(amount, min_amount_out)?;
 
         msg!("Market: {} {}. Reserve vefore: {}. Reserve after: {}",
             market.id,
@@ -1,6 +1,5 @@
 use anchor_lang::prelude::*;
 use crate::errors::CustomError;
-use crate::math::lp_shares::*;
 use crate::state::market::*;
 use crate::state::event::*;
 use anchor_spl::associated_token::AssociatedToken;
@@ -77,30 +76,14 @@ pub struct MarketWithdraw<'info> {
 impl MarketWithdraw<'_> {
     pub fn handle(ctx: Context<MarketWithdraw>, params: WithdrawParams) -> Result<()> {
         let market = &mut ctx.accounts.market;
-
-        let (withdraw_amount, lp_tokens_to_burn) = calc_withdraw_amount_from_lp_shares(params.lp_tokens_to_burn, &market)?;
-        require!(withdraw_amount >= params.min_amount_out, CustomError::SlippageExceeded);
-
-        let market_tvl = market.reserve_supply.checked_add(market.premiums).unwrap();
-        let uncomitted_reserve = market.reserve_supply.checked_sub(market.committed_reserve).unwrap();
-
-        let reserve_share = if market_tvl > 0 {
-            (withdraw_amount as u128)
-                .checked_mul(uncomitted_reserve as u128).unwrap()
-                .checked_div(market_tvl as u128).unwrap() as u64
-        } else {
-            0
-        };
-
-        let premium_share = withdraw_amount.checked_sub(reserve_share).unwrap();
+        require!(!market.is_paused(MARKET_PAUSE_WITHDRAW), CustomError::MarketPaused);
 
         let reserve_before = market.reserve_supply;
         let premiums_before = market.premiums;
         let lp_tokens_before = market.lp_minted;
 
-        market.reserve_supply = market.reserve_supply.checked_sub(reserve_share).unwrap();
-        market.premiums = market.premiums.checked_sub(premium_share).unwrap();
-        market.lp_minted = market.lp_minted.checked_sub(params.lp_tokens_to_burn).unwrap();
+        let (withdraw_amount, lp_tokens_to_burn) = market.remove_liquidity(params.lp_tokens_to_burn, params.min_amount_out)?;
+        msg!("Requested tokens to burn: {}, actual tokens to burn: {}", params.lp_tokens_to_burn, lp_tokens_to_burn);
 
         //Market vault signer seeds
         let ix_bytes = params.ix.to_le_bytes();
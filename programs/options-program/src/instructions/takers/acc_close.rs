@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::common::OptionType;
+use crate::errors::CustomError;
+use crate::state::event::*;
+use crate::state::market::*;
+use crate::state::user_account::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AccountCloseParams {
+    pub page: u8,                   // Position page to close
+}
+
+#[derive(Accounts)]
+#[instruction(params: AccountCloseParams)]
+pub struct AccountClose<'info> {
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+            USR_ACC_SEED.as_bytes(),
+            signer.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
+        ],
+        bump
+    )]
+    pub account: AccountLoader<'info, UserAccount>,
+    pub system_program: Program<'info, System>
+    // remaining_accounts: markets of the lapsed options still in the account, so their collateral is released first
+}
+
+impl<'info> AccountClose<'info> {
+    pub fn handle(ctx: Context<'_, '_, 'info, 'info, AccountClose<'info>>, params: AccountCloseParams) -> Result<()> {
+        let user_account = &mut ctx.accounts.account.load_mut()?;
+        let stamp_now = Clock::get()?.unix_timestamp;
+
+        //Only options past their market's exercise window can be left in the account, they are settled as settle_expired
+        //would. Live positions must be exercised or sold back first
+        for option in user_account.options.iter_mut().filter(|o| o.is_initialized()) {
+            let market_ix_bytes = option.market_ix.to_le_bytes();
+            let (market_address, _) = Pubkey::find_program_address(&[MARKET_SEED.as_bytes(), market_ix_bytes.as_ref()], ctx.program_id);
+            let market_info = ctx.remaining_accounts.iter()
+                .find(|a| a.key() == market_address)
+                .ok_or(CustomError::InvalidState)?;
+
+            let mut market = Account::<Market>::try_from(market_info)?;
+            require!(option.is_lapsed(stamp_now, market.exercise_window()), CustomError::AccountHasOpenPositions);
+
+            let released_collateral = option.max_potential_payout_in_tokens;
+            market.release_collateral(released_collateral)?;
+            market.exit(ctx.program_id)?;
+
+            msg!("Option {} of user {} expired. Released collateral: {}", option.ix, ctx.accounts.signer.key(), released_collateral);
+
+            emit!(OptionExpired {
+                user: ctx.accounts.signer.key(),
+                market: option.market_ix,
+                page: params.page,
+                option_ix: option.ix,
+                option: OptionType::try_from(option.option_type).unwrap(),
+                timestamp: stamp_now,
+                quantity: option.quantity,
+                released_collateral,
+            });
+
+            option.clear();
+        }
+
+        msg!("Position page {} of {} closed", params.page, ctx.accounts.signer.key());
+
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
 use anchor_lang::prelude::*;
 use crate::state::user_account::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct AccountCreateParams {
+    pub page: u8,                   // Position page to create, 0 is the user's first account
+}
+
 #[derive(Accounts)]
+#[instruction(params: AccountCreateParams)]
 pub struct AccountCreate<'info> {
 
     #[account(mut)]
@@ -12,7 +18,8 @@ pub struct AccountCreate<'info> {
         payer = signer,
         seeds = [
             USR_ACC_SEED.as_bytes(),
-            signer.key().as_ref()
+            signer.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
         ],
         bump,
         space = 8 + UserAccount::INIT_SPACE
@@ -22,7 +29,8 @@ pub struct AccountCreate<'info> {
 }
 
 impl AccountCreate<'_> {
-    pub fn handle(_ctx: Context<AccountCreate>) -> Result<()> {
+    pub fn handle(ctx: Context<AccountCreate>, params: AccountCreateParams) -> Result<()> {
+        msg!("Position page {} created for {}", params.page, ctx.accounts.signer.key());
         Ok(())
     }
 }
\ No newline at end of file
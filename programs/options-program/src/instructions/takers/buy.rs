@@ -1,19 +1,46 @@
 use anchor_lang::prelude::*;
-use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2};
 use anchor_spl::token_interface::{self, *};
-use crate::{common::*, errors::CustomError, math::premium::*, state::{event::OptionBought, market::*, user_account::*}};
+use crate::{common::*, errors::CustomError, instructions::takers::quote::OptionQuote, state::{event::OptionBought, market::*, user_account::*}};
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct BuyOptionParams {
     pub market_ix: u16,
+    pub page: u8,                   // Position page of the user account
     pub option: OptionType,
     pub spot_deviation: SpotDeviation,  
-    pub expiry_setting: Expiry,
-    pub quantity: u64
+    pub expiry_setting: ExpirySetting,
+    pub quantity: u64,
+    pub max_premium_tokens: u64,                    // Max premium paid, in asset smallest units
+    pub max_spot_deviation: Option<SpotSlippage>
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyOptionAtStrikeParams {
+    pub market_ix: u16,
+    pub page: u8,                   // Position page of the user account
+    pub option: OptionType,
+    pub strike_price: u64,              // usd scaled by 10^8, must be a multiple of the market strike tick size
+    pub expiry_setting: ExpirySetting,
+    pub quantity: u64,
+    pub max_premium_tokens: u64,
+    pub max_spot_deviation: Option<SpotSlippage>
+}
+
+//Order fields shared by both buy params
+struct OptionRequest {
+    market_ix: u16,
+    page: u8,
+    option: OptionType,
+    expiry_setting: ExpirySetting,
+    quantity: u64,
+    max_premium_tokens: u64,
+    max_spot_deviation: Option<SpotSlippage>
+}
+
+//Both buy params start with market_ix and page, which is all the account constraints need
 #[derive(Accounts)]
-#[instruction(params: BuyOptionParams)]
+#[instruction(market_ix: u16, page: u8)]
 pub struct BuyOption<'info> {
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -22,7 +49,8 @@ pub struct BuyOption<'info> {
         mut,
         seeds = [
             USR_ACC_SEED.as_bytes(),
-            signer.key().as_ref()
+            signer.key().as_ref(),
+            UserAccount::page_seed(page).as_ref()
         ],
         bump
     )]
@@ -39,7 +67,7 @@ pub struct BuyOption<'info> {
         mut,
         seeds = [
             MARKET_SEED.as_bytes(),
-            params.market_ix.to_le_bytes().as_ref()
+            market_ix.to_le_bytes().as_ref()
         ],
         bump,
     )]
@@ -49,7 +77,7 @@ pub struct BuyOption<'info> {
         mut,
         seeds = [
             MARKET_VAULT_SEED.as_bytes(),
-            params.market_ix.to_le_bytes().as_ref()
+            market_ix.to_le_bytes().as_ref()
         ],
         bump,
     )]
@@ -59,7 +87,7 @@ pub struct BuyOption<'info> {
         mut,
         seeds = [
             PROTOCOL_FEES_VAULT_SEED.as_bytes(),
-            params.market_ix.to_le_bytes().as_ref()
+            market_ix.to_le_bytes().as_ref()
         ],
         bump,
     )]
@@ -75,49 +103,63 @@ pub struct BuyOption<'info> {
 
 impl BuyOption<'_> {
     pub fn handle(ctx: Context<BuyOption>, params: BuyOptionParams) -> Result<()> {
+        let spot_deviation = params.spot_deviation;
+        let request = OptionRequest {
+            market_ix: params.market_ix,
+            page: params.page,
+            option: params.option,
+            expiry_setting: params.expiry_setting,
+            quantity: params.quantity,
+            max_premium_tokens: params.max_premium_tokens,
+            max_spot_deviation: params.max_spot_deviation
+        };
+        Self::open_option(ctx, request,
+            |market, price| spot_deviation.convert_to_strike(price.price as u128, market.strike_tick_size as u128))
+    }
+
+    pub fn handle_at_strike(ctx: Context<BuyOption>, params: BuyOptionAtStrikeParams) -> Result<()> {
+        let strike_price = params.strike_price as u128;
+        let request = OptionRequest {
+            market_ix: params.market_ix,
+            page: params.page,
+            option: params.option,
+            expiry_setting: params.expiry_setting,
+            quantity: params.quantity,
+            max_premium_tokens: params.max_premium_tokens,
+            max_spot_deviation: params.max_spot_deviation
+        };
+        Self::open_option(ctx, request,
+            |market, price| {
+                market.validate_strike(strike_price, price.price as u128)?;
+                Ok(strike_price)
+            })
+    }
+
+    fn open_option(
+        ctx: Context<BuyOption>,
+        request: OptionRequest,
+        select_strike: impl FnOnce(&Market, &Price) -> Result<u128>
+    ) -> Result<()> {
+        let OptionRequest { market_ix, page, option, expiry_setting, quantity, max_premium_tokens, max_spot_deviation } = request;
         let user_account = &mut ctx.accounts.account.load_mut()?;
         let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_BUY), CustomError::MarketPaused);
 
         //Check avaiable slots in array
         let slot_ix = user_account.get_available_slot()
             .ok_or(CustomError::OrdersLimitExceeded)?;
-        let clock = Clock::get()?;
-        let option_expiry = clock.unix_timestamp + params.expiry_setting.to_seconds().unwrap() as i64;
 
-        //Get asset price from oracle in usd, scaled by 10^8 (Pyth)
-        let price_update = &mut ctx.accounts.price_update;
+        //Same pricing as quote_option
+        let quote = OptionQuote::new(market, &ctx.accounts.price_update, &Clock::get()?, option, expiry_setting, quantity, select_strike)?;
+        if let Some(max_spot_deviation) = max_spot_deviation {
+            max_spot_deviation.check(quote.spot_price_usd as u128)?;
+        }
 
-        // Using increased, suboptimal, maximum age, because we are working with cloned pyth account w stale updated price 
-        let maximum_age: u64 = 100 * 60;
-        // let maximum_age: u64 = 90; //90 sec for mainnet
-
-        let feed_id = get_feed_id_from_hex(market.price_feed.as_str())?;
-        let price = price_update.get_price_no_older_than(&clock, maximum_age, &feed_id)?;
-
-        //In serious production settings this should be checked for freshness
-        // require!(market.vol_last_updated + 120 >= clock.unix_timestamp, CustomError::VolatilityStaled);
-
-        let strike_price_usd = params.spot_deviation.convert_to_strike(price.price as u128).unwrap();
-        let (_, total_collateral_tokens) = calculate_collateral(
-            strike_price_usd,
-            price.price as u128,
-            &params.option,
-            &market,
-            params.expiry_setting,
-            params.quantity
-        )?;
+        let OptionQuote { strike_price_usd, expiry: option_expiry, premium_usd, premium_tokens, fee_tokens, collateral_tokens: total_collateral_tokens, .. } = quote;
 
         let available_collateral = market.reserve_supply - market.committed_reserve;
         require!(available_collateral > total_collateral_tokens, CustomError::InsufficientColateral);  
-        
-        //Premium
-        let (premium_usd, premium_tokens, fee_tokens) = calculate_option_premium(
-            strike_price_usd,
-            price.price as u128,
-            params.expiry_setting,
-            market, 
-            &params.option,
-            params.quantity)?;        
+        require!(premium_tokens <= max_premium_tokens, CustomError::SlippageExceeded);
 
         let lp_share = premium_tokens - fee_tokens;
 
@@ -132,10 +174,10 @@ impl BuyOption<'_> {
                 authority: ctx.accounts.signer.to_account_info(),
                 mint: ctx.accounts.asset_mint.to_account_info()
             }),
-            premium_tokens as u64,
+            premium_tokens,
             ctx.accounts.asset_mint.decimals)?;
 
-        let market_ix_bytes = params.market_ix.to_le_bytes();
+        let market_ix_bytes = market_ix.to_le_bytes();
         //Transfer protocol fees to fee vault
         let signer_seeds: &[&[&[u8]]] = &[&[
             MARKET_VAULT_SEED.as_bytes(),
@@ -153,21 +195,18 @@ impl BuyOption<'_> {
             fee_tokens,
             ctx.accounts.asset_mint.decimals)?;
 
-        market.premiums = market.premiums
-            .checked_add(lp_share).ok_or(CustomError::Overflow)?;
-        market.committed_reserve = market.committed_reserve
-            .checked_add(total_collateral_tokens).ok_or(CustomError::Overflow)?;
+        market.accrue_premium(lp_share, total_collateral_tokens)?;
 
         //Save user option
         user_account.options[slot_ix] = OptionOrder {
-            strike_price: strike_price_usd as u64, //TODO u64::tryinto() better approach
+            strike_price: strike_price_usd,
             expiry: option_expiry,
             premium: premium_tokens,
             premium_in_usd: premium_usd,
-            quantity: params.quantity,
+            quantity,
             max_potential_payout_in_tokens: total_collateral_tokens,
-            market_ix: params.market_ix,
-            option_type: u8::from(params.option),
+            market_ix,
+            option_type: u8::from(option),
             ix: slot_ix as u8,
             is_used: 1,
             padding: [0_u8; 3]
@@ -185,25 +224,26 @@ impl BuyOption<'_> {
         user: {}
         ",
         slot_ix,
-        params.market_ix,
-        params.quantity,
+        market_ix,
+        quantity,
         premium_tokens,
         premium_usd,
-        price.price,
+        quote.spot_price_usd,
         strike_price_usd,
-        params.option.clone(),
+        option.clone(),
         ctx.accounts.signer.key());
 
         //Off-chain proccess could listen for those events and schedule authorized exercise at expiry time on user(taker)'s behalf for convenience...
         emit!(OptionBought {
-            market: params.market_ix,
+            market: market_ix,
             expiry_stamp: option_expiry,
             max_potential_payout_in_tokens: total_collateral_tokens,
-            quantity: params.quantity,
-            strike_price_usd: strike_price_usd as u64, //TODO u64::tryinto() better approach
-            bought_at_price_usd: price.price as u64, 
-            option: params.option.clone(),
+            quantity,
+            strike_price_usd,
+            bought_at_price_usd: quote.spot_price_usd,
+            option: option.clone(),
             user: ctx.accounts.signer.key(),
+            page,
             option_ix: slot_ix as u8
         });
 
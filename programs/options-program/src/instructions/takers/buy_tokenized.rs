@@ -0,0 +1,226 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{self, *};
+use crate::{common::*, errors::CustomError, instructions::takers::quote::OptionQuote, state::{event::OptionTokensBought, market::*, option_series::*}};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct BuyTokenizedParams {
+    pub market_ix: u16,
+    pub option: OptionType,
+    pub strike_price: u64,              // usd scaled by 10^8, must be a multiple of the market strike tick size
+    pub expiry_setting: ExpirySetting,
+    pub expiry: i64,                    // Expiry the setting resolves to, part of the series address
+    pub quantity: u64,
+    pub max_premium_tokens: u64,        // Max premium paid, in asset smallest units
+    pub max_spot_deviation: Option<SpotSlippage>
+}
+
+#[derive(Accounts)]
+#[instruction(params: BuyTokenizedParams)]
+pub struct BuyTokenized<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = signer
+    )]
+    pub user_token_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [
+            OPTION_SERIES_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref(),
+            &[u8::from(params.option)],
+            params.strike_price.to_le_bytes().as_ref(),
+            params.expiry.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + OptionSeries::INIT_SPACE
+    )]
+    pub option_series: Account<'info, OptionSeries>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        seeds = [
+            OPTION_MINT_SEED.as_bytes(),
+            option_series.key().as_ref()
+        ],
+        bump,
+        mint::decimals = 0,
+        mint::authority = option_series,
+        mint::token_program = token_program
+    )]
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = option_mint,
+        associated_token::authority = signer,
+        associated_token::token_program = token_program
+    )]
+    pub user_option_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+        constraint = asset_mint.key() == market.asset_mint
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_VAULT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            PROTOCOL_FEES_VAULT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub protocol_fees_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl BuyTokenized<'_> {
+    pub fn handle(ctx: Context<BuyTokenized>, params: BuyTokenizedParams) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_BUY), CustomError::MarketPaused);
+
+        //Same pricing as quote_option
+        let strike_price_usd = params.strike_price as u128;
+        let quote = OptionQuote::new(market, &ctx.accounts.price_update, &Clock::get()?, params.option, params.expiry_setting, params.quantity,
+            |market, price| {
+                market.validate_strike(strike_price_usd, price.price as u128)?;
+                Ok(strike_price_usd)
+            })?;
+
+        //The series address is derived from the expiry, so it must be the one the setting currently resolves to
+        let option_expiry = quote.expiry;
+        require!(option_expiry == params.expiry, CustomError::InvalidExpiry);
+        if let Some(max_spot_deviation) = params.max_spot_deviation {
+            max_spot_deviation.check(quote.spot_price_usd as u128)?;
+        }
+
+        let OptionQuote { premium_tokens, fee_tokens, collateral_tokens: total_collateral_tokens, .. } = quote;
+
+        let available_collateral = market.reserve_supply - market.committed_reserve;
+        require!(available_collateral > total_collateral_tokens, CustomError::InsufficientColateral);
+        require!(premium_tokens <= params.max_premium_tokens, CustomError::SlippageExceeded);
+
+        let lp_share = premium_tokens - fee_tokens;
+
+        //Transfer premium to market vault
+        token_interface::transfer_checked(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.user_token_acc.to_account_info(),
+                to: ctx.accounts.market_vault.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info()
+            }),
+            premium_tokens,
+            ctx.accounts.asset_mint.decimals)?;
+
+        let market_ix_bytes = params.market_ix.to_le_bytes();
+        //Transfer protocol fees to fee vault
+        let vault_signer_seeds: &[&[&[u8]]] = &[&[
+            MARKET_VAULT_SEED.as_bytes(),
+            market_ix_bytes.as_ref(),
+            &[ctx.bumps.market_vault]]];
+
+        token_interface::transfer_checked(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.market_vault.to_account_info(),
+                to: ctx.accounts.protocol_fees_vault.to_account_info(),
+                authority: ctx.accounts.market_vault.to_account_info(),
+                mint: ctx.accounts.asset_mint.to_account_info()
+            }).with_signer(vault_signer_seeds),
+            fee_tokens,
+            ctx.accounts.asset_mint.decimals)?;
+
+        market.accrue_premium(lp_share, total_collateral_tokens)?;
+
+        //First buy of the series
+        let option_series = &mut ctx.accounts.option_series;
+        if !option_series.is_initialized() {
+            option_series.market_ix = params.market_ix;
+            option_series.option_type = u8::from(params.option);
+            option_series.strike_price = params.strike_price;
+            option_series.expiry = option_expiry;
+            option_series.mint = ctx.accounts.option_mint.key();
+            option_series.bump = ctx.bumps.option_series;
+        }
+        option_series.add(params.quantity, total_collateral_tokens)?;
+
+        //Mint option tokens to the buyer
+        let option_type_bytes = [option_series.option_type];
+        let strike_bytes = option_series.strike_price.to_le_bytes();
+        let expiry_bytes = option_series.expiry.to_le_bytes();
+        let series_signer_seeds: &[&[&[u8]]] = &[&[
+            OPTION_SERIES_SEED.as_bytes(),
+            market_ix_bytes.as_ref(),
+            option_type_bytes.as_ref(),
+            strike_bytes.as_ref(),
+            expiry_bytes.as_ref(),
+            &[option_series.bump]]];
+
+        token_interface::mint_to(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                MintTo {
+                    mint: ctx.accounts.option_mint.to_account_info(),
+                    to: ctx.accounts.user_option_ata.to_account_info(),
+                    authority: option_series.to_account_info()
+                },
+                series_signer_seeds),
+            params.quantity)?;
+
+        msg!("Minted {} option tokens of series {} to {}. Premium: {}, collateral: {}",
+            params.quantity,
+            option_series.key(),
+            ctx.accounts.signer.key(),
+            premium_tokens,
+            total_collateral_tokens);
+
+        emit!(OptionTokensBought {
+            user: ctx.accounts.signer.key(),
+            market: params.market_ix,
+            series: option_series.key(),
+            mint: option_series.mint,
+            option: params.option,
+            strike_price_usd: params.strike_price,
+            bought_at_price_usd: quote.spot_price_usd,
+            expiry_stamp: option_expiry,
+            quantity: params.quantity,
+            premium: premium_tokens,
+            max_potential_payout_in_tokens: total_collateral_tokens
+        });
+
+        Ok(())
+    }
+}
@@ -1,18 +1,20 @@
-use core::cmp::min;
 use crate::common::OptionType;
-use crate::constants::EXERCISE_INTERVAL_TOLERANCE;
 use crate::errors::*;
+use crate::math::payout::calculate_exercise_payout;
 use crate::state::event::*;
 use crate::state::user_account::*;
 use crate::state::market::*;
+use crate::state::settlement::Settlement;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
-use pyth_solana_receiver_sdk::price_update::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct ExerciseOptionParams {
     pub market_ix: u16,
-    pub option_id: u8
+    pub page: u8,                   // Position page of the user account
+    pub option_id: u8,
+    pub quantity: u64               // Units to exercise, the rest stays open
 }
 
 #[derive(Accounts)]
@@ -26,7 +28,8 @@ pub struct ExerciseOption<'info> {
         mut,
         seeds = [
             USR_ACC_SEED.as_bytes(),
-            signer.key().as_ref()
+            signer.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
         ],
         bump
     )]
@@ -61,57 +64,39 @@ pub struct ExerciseOption<'info> {
 
     pub asset_mint: InterfaceAccount<'info, Mint>,
     pub price_update: Account<'info, PriceUpdateV2>,
+
+    // Settlement of the option's expiry, required for European style markets
+    pub settlement: Option<Account<'info, Settlement>>,
     pub token_program: Interface<'info, TokenInterface>,
     
 }
 
 impl ExerciseOption<'_> {
-    pub fn handle(ctx: Context<ExerciseOption>, market_ix: u16, option_id: u8) -> Result<()> {
+    pub fn handle(ctx: Context<ExerciseOption>, market_ix: u16, page: u8, option_id: u8, quantity: u64) -> Result<()> {
         let user_account = &mut ctx.accounts.account.load_mut()?;
         let market = &mut ctx.accounts.market;
-        let option = &mut user_account.options[option_id as usize];
+        require!(!market.is_paused(MARKET_PAUSE_EXERCISE), CustomError::MarketPaused);
+
+        let slot = user_account.options.get_mut(option_id as usize).ok_or(CustomError::InvalidState)?;
+        require!(slot.is_initialized() && slot.market_ix == market_ix, CustomError::InvalidState);
 
-        let mut user_payout_in_tokens = 0u64;
         let stamp_now = Clock::get()?.unix_timestamp;
 
         //Commented constraint for expiration date for the purpose of easier demo...
         //require!(stamp_now >= option.expiry - EXERCISE_INTERVAL_TOLERANCE, CustomError::ExerciseTooEarly);
-        require!(stamp_now <= option.expiry + EXERCISE_INTERVAL_TOLERANCE, CustomError::ExerciseIsOverdue);
-
-        //Get asset price from oracle in usd, scaled by 10^6 (Pyth)
-        let price_update = &mut ctx.accounts.price_update;
-
-        // Using increased, suboptimal, maximum age, because we are working with cloned pyth account w stale updated price 
-        // let maximum_age: u64 = 100* 60; 
-        let maximum_age: u64 = 90; //90 sec for mainnet
-
-        let feed_id = get_feed_id_from_hex(market.price_feed.as_str())?;
-        let price = price_update.get_price_no_older_than(&Clock::get()?, maximum_age, &feed_id)?;
-        
-        let profit_usd = match OptionType::try_from(option.option_type).unwrap() {
-            OptionType::CALL => {
-                (price.price as u64)
-                    .saturating_sub(option.strike_price) 
-                    .checked_mul(option.quantity).unwrap()
-            },
-            OptionType::PUT => {
-                option.strike_price
-                    .saturating_sub(price.price as u64)
-                    .checked_mul(option.quantity).unwrap()
-            }
-        };
+        require!(stamp_now <= slot.expiry + market.exercise_window(), CustomError::ExerciseIsOverdue);
 
-        // If profitable, transfer token equivalent from vault
-        if profit_usd > 0 {
-            let token_scaling = 10_u64.pow(market.asset_decimals as u32);
+        //Get asset price in usd, normalized to 10^8. Oracle (spot or EMA) price for American, recorded settlement price for European style
+        let exercise_price = market.get_exercise_price(slot.expiry, &ctx.accounts.price_update, ctx.accounts.settlement.as_deref(), &Clock::get()?)?;
 
-            let profit_in_tokens = (profit_usd as u128)
-                .checked_mul(token_scaling as u128).unwrap()
-                .checked_div(price.price as u128).unwrap() as u64;
+        //Exercised part of the option, with its share of the collateral
+        let option = slot.take(quantity)?;
+        let remaining_quantity = slot.quantity;
 
-            //There is limit to payouts for solvency
-            user_payout_in_tokens = min(profit_in_tokens, option.max_potential_payout_in_tokens);
+        let (profit_usd, user_payout_in_tokens) = calculate_exercise_payout(&option, exercise_price, market)?;
 
+        // If profitable, transfer token equivalent from vault
+        if user_payout_in_tokens > 0 {
             let market_ix_bytes = market_ix.to_le_bytes();
 
             let signer_seeds: &[&[&[u8]]] = &[&[
@@ -131,42 +116,33 @@ impl ExerciseOption<'_> {
                 ctx.accounts.asset_mint.decimals)?;            
 
             //Update market reserve n premiums data
-            if user_payout_in_tokens <= market.premiums {
-                market.premiums = market.premiums
-                    .checked_sub(user_payout_in_tokens)
-                    .ok_or(CustomError::Overflow)?;
-            } else {
-                let remainder = user_payout_in_tokens - market.premiums;
-                market.premiums = 0;
-                market.reserve_supply = market.reserve_supply
-                    .checked_sub(remainder)
-                    .ok_or(CustomError::Overflow)?;
-            }
+            market.pay_out(user_payout_in_tokens)?;
         } 
 
         //Release commited reserve
-        market.committed_reserve = market.committed_reserve
-                .checked_sub(option.max_potential_payout_in_tokens)
-                .ok_or(CustomError::Overflow)?;
+        market.release_collateral(option.max_potential_payout_in_tokens)?;
 
-        //clear option slot         
-        option.clear();        
+        let option_type = OptionType::try_from(option.option_type).unwrap();
 
         //log
-        msg!("User {} exercised option {} ", ctx.accounts.signer.key().to_string(), option_id);
+        msg!("User {} exercised {} of option {}, remaining {}", ctx.accounts.signer.key().to_string(), quantity, option_id, remaining_quantity);
         msg!("Payout usd (in 10^8) {} ", profit_usd);
         msg!("Payout token amount {} ", user_payout_in_tokens);
-        msg!("Option type {:?} ", option.option_type);
+        msg!("Option type {:?} ", option_type);
 
         //emit event
         emit!(OptionExercised {
             market: market_ix,
-            quantity: option.quantity,
-            option: OptionType::try_from(option.option_type).unwrap(),
+            quantity,
+            remaining_quantity,
+            option: option_type,
             user: ctx.accounts.signer.key(),
-            option_ix: option_id as u8,
-            profit_usd: profit_usd,
+            exercised_by: ctx.accounts.signer.key(),
+            page,
+            option_ix: option_id,
+            profit_usd,
             user_payout: user_payout_in_tokens,
+            keeper_bounty: 0,
             timestamp: stamp_now
         });
 
@@ -0,0 +1,166 @@
+use crate::common::OptionType;
+use crate::constants::BASIS_POINTS_DENOMINATOR;
+use crate::errors::*;
+use crate::math::payout::calculate_exercise_payout;
+use crate::state::event::*;
+use crate::state::user_account::*;
+use crate::state::market::*;
+use crate::state::settlement::Settlement;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExerciseForParams {
+    pub market_ix: u16,
+    pub page: u8,                   // Position page of the user account
+    pub option_id: u8
+}
+
+#[derive(Accounts)]
+#[instruction(params: ExerciseForParams)]
+pub struct ExerciseFor<'info> {
+
+    // Permissionless crank, anyone can exercise expired options on the holder's behalf
+    pub keeper: Signer<'info>,
+
+    /// CHECK: Option holder wallet, only used to derive the user account and validate the payout token account
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USR_ACC_SEED.as_bytes(),
+            owner.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
+        ],
+        bump
+    )]
+    pub account: AccountLoader<'info, UserAccount>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = owner
+    )]
+    pub owner_token_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+    )]
+    pub keeper_token_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_VAULT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    // Settlement of the option's expiry, required for European style markets
+    pub settlement: Option<Account<'info, Settlement>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl ExerciseFor<'_> {
+    pub fn handle(ctx: Context<ExerciseFor>, market_ix: u16, page: u8, option_id: u8) -> Result<()> {
+        let user_account = &mut ctx.accounts.account.load_mut()?;
+        let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_EXERCISE), CustomError::MarketPaused);
+
+        let option = user_account.options.get_mut(option_id as usize).ok_or(CustomError::InvalidState)?;
+        require!(option.is_initialized() && option.market_ix == market_ix, CustomError::InvalidState);
+
+        //Keepers can only step in once the option has expired
+        let stamp_now = Clock::get()?.unix_timestamp;
+        require!(stamp_now >= option.expiry, CustomError::ExerciseTooEarly);
+        require!(stamp_now <= option.expiry + market.exercise_window(), CustomError::ExerciseIsOverdue);
+
+        let exercise_price = market.get_exercise_price(option.expiry, &ctx.accounts.price_update, ctx.accounts.settlement.as_deref(), &Clock::get()?)?;
+
+        let (profit_usd, payout_in_tokens) = calculate_exercise_payout(option, exercise_price, market)?;
+
+        //Keeper bounty is taken out of the holder's payout
+        let keeper_bounty = payout_in_tokens
+            .checked_mul(market.keeper_bounty_bps).ok_or(CustomError::Overflow)?
+            .checked_div(BASIS_POINTS_DENOMINATOR).ok_or(CustomError::Overflow)?;
+        let owner_payout = payout_in_tokens - keeper_bounty;
+
+        let market_ix_bytes = market_ix.to_le_bytes();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            MARKET_VAULT_SEED.as_bytes(),
+            market_ix_bytes.as_ref(),
+            &[ctx.bumps.market_vault]]];
+
+        if owner_payout > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.market_vault.to_account_info(),
+                    to: ctx.accounts.owner_token_acc.to_account_info(),
+                    authority: ctx.accounts.market_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info()
+                }).with_signer(signer_seeds),
+                owner_payout,
+                ctx.accounts.asset_mint.decimals)?;
+        }
+
+        if keeper_bounty > 0 {
+            token_interface::transfer_checked(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.market_vault.to_account_info(),
+                    to: ctx.accounts.keeper_token_acc.to_account_info(),
+                    authority: ctx.accounts.market_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info()
+                }).with_signer(signer_seeds),
+                keeper_bounty,
+                ctx.accounts.asset_mint.decimals)?;
+        }
+
+        market.pay_out(payout_in_tokens)?;
+        market.release_collateral(option.max_potential_payout_in_tokens)?;
+
+        let option_type = OptionType::try_from(option.option_type).unwrap();
+        let quantity = option.quantity;
+
+        option.clear();
+
+        msg!("Keeper {} exercised option {} of user {}", ctx.accounts.keeper.key(), option_id, ctx.accounts.owner.key());
+        msg!("Payout usd (in 10^8) {}, owner payout tokens {}, keeper bounty tokens {}", profit_usd, owner_payout, keeper_bounty);
+
+        emit!(OptionExercised {
+            market: market_ix,
+            quantity,
+            remaining_quantity: 0,
+            option: option_type,
+            user: ctx.accounts.owner.key(),
+            exercised_by: ctx.accounts.keeper.key(),
+            page,
+            option_ix: option_id,
+            profit_usd,
+            user_payout: owner_payout,
+            keeper_bounty,
+            timestamp: stamp_now
+        });
+
+        Ok(())
+    }
+}
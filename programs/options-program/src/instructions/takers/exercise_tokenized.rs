@@ -0,0 +1,156 @@
+use crate::common::OptionType;
+use crate::errors::*;
+use crate::math::payout::calculate_exercise_payout;
+use crate::state::event::*;
+use crate::state::market::*;
+use crate::state::option_series::*;
+use crate::state::settlement::Settlement;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Burn, Mint, TokenAccount, TokenInterface, TransferChecked };
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ExerciseTokenizedParams {
+    pub market_ix: u16,
+    pub option: OptionType,
+    pub strike_price: u64,
+    pub expiry: i64,
+    pub quantity: u64               // Option tokens to burn
+}
+
+#[derive(Accounts)]
+#[instruction(params: ExerciseTokenizedParams)]
+pub struct ExerciseTokenized<'info> {
+
+    // Any holder of the option tokens, not necessarily the buyer
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = signer
+    )]
+    pub user_token_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = option_mint,
+        token::authority = signer
+    )]
+    pub user_option_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            OPTION_SERIES_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref(),
+            &[u8::from(params.option)],
+            params.strike_price.to_le_bytes().as_ref(),
+            params.expiry.to_le_bytes().as_ref()
+        ],
+        bump = option_series.bump,
+    )]
+    pub option_series: Account<'info, OptionSeries>,
+
+    #[account(
+        mut,
+        address = option_series.mint
+    )]
+    pub option_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_VAULT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    // Settlement of the series expiry, required for European style markets
+    pub settlement: Option<Account<'info, Settlement>>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl ExerciseTokenized<'_> {
+    pub fn handle(ctx: Context<ExerciseTokenized>, params: ExerciseTokenizedParams) -> Result<()> {
+        let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_EXERCISE), CustomError::MarketPaused);
+
+        let option_series = &mut ctx.accounts.option_series;
+        let stamp_now = Clock::get()?.unix_timestamp;
+        require!(stamp_now <= option_series.expiry + market.exercise_window(), CustomError::ExerciseIsOverdue);
+
+        let exercise_price = market.get_exercise_price(option_series.expiry, &ctx.accounts.price_update, ctx.accounts.settlement.as_deref(), &Clock::get()?)?;
+
+        //Exercised tokens, with their share of the series collateral
+        let option = option_series.take(params.quantity)?;
+
+        let (profit_usd, user_payout_in_tokens) = calculate_exercise_payout(&option, exercise_price, market)?;
+
+        //Burn the exercised option tokens
+        token_interface::burn(
+            CpiContext::new(ctx.accounts.token_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.option_mint.to_account_info(),
+                from: ctx.accounts.user_option_acc.to_account_info(),
+                authority: ctx.accounts.signer.to_account_info()
+            }),
+            params.quantity)?;
+
+        if user_payout_in_tokens > 0 {
+            let market_ix_bytes = params.market_ix.to_le_bytes();
+
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                MARKET_VAULT_SEED.as_bytes(),
+                market_ix_bytes.as_ref(),
+                &[ctx.bumps.market_vault]]];
+
+            token_interface::transfer_checked(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.market_vault.to_account_info(),
+                    to: ctx.accounts.user_token_acc.to_account_info(),
+                    authority: ctx.accounts.market_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info()
+                }).with_signer(signer_seeds),
+                user_payout_in_tokens,
+                ctx.accounts.asset_mint.decimals)?;
+
+            market.pay_out(user_payout_in_tokens)?;
+        }
+
+        market.release_collateral(option.max_potential_payout_in_tokens)?;
+
+        msg!("User {} exercised {} option tokens of series {}", ctx.accounts.signer.key(), params.quantity, option_series.key());
+        msg!("Payout usd (in 10^8) {}, payout tokens {}", profit_usd, user_payout_in_tokens);
+
+        emit!(OptionTokensExercised {
+            user: ctx.accounts.signer.key(),
+            market: params.market_ix,
+            series: option_series.key(),
+            option: params.option,
+            timestamp: stamp_now,
+            quantity: params.quantity,
+            profit_usd,
+            user_payout: user_payout_in_tokens
+        });
+
+        Ok(())
+    }
+}
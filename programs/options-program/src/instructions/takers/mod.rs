@@ -1,3 +1,12 @@
 pub mod acc_create;
+pub mod acc_close;
 pub mod buy;
-pub mod exercise;
\ No newline at end of file
+pub mod quote;
+pub mod exercise;
+pub mod exercise_for;
+pub mod settle_expired;
+pub mod record_settlement;
+pub mod sell_back;
+pub mod buy_tokenized;
+pub mod exercise_tokenized;
+pub mod settle_expired_series;
\ No newline at end of file
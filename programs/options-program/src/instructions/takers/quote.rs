@@ -0,0 +1,124 @@
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2};
+use crate::{common::*, errors::CustomError, math::premium::*, state::market::*};
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct QuoteParams {
+    pub market_ix: u16,
+    pub option: OptionType,
+    pub strike: StrikeSetting,
+    pub expiry_setting: ExpirySetting,
+    pub quantity: u64
+}
+
+//Price of an option as a buy would fill it at the current oracle price and volatility surface
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct OptionQuote {
+    pub strike_price_usd: u64,          // usd scaled by 10^8
+    pub expiry: i64,
+    pub spot_price_usd: u64,            // Oracle mid price, usd scaled by 10^8
+    pub premium_usd: u64,
+    pub premium_tokens: u64,            // Token smallest units, fee included
+    pub fee_tokens: u64,
+    pub collateral_usd: u64,
+    pub collateral_tokens: u64,         // Reserve committed for the option
+}
+
+impl OptionQuote {
+    //Pricing shared by quotes and buys. Strike is set off the mid price, premium and collateral are priced
+    //at the buyer's worse side of the confidence interval
+    pub fn new(
+        market: &Market,
+        price_update: &PriceUpdateV2,
+        clock: &Clock,
+        option: OptionType,
+        expiry_setting: ExpirySetting,
+        quantity: u64,
+        select_strike: impl FnOnce(&Market, &Price) -> Result<u128>
+    ) -> Result<Self> {
+        let expiry = market.get_expiry_stamp(&expiry_setting, clock.unix_timestamp)?;
+        let time_to_expiry_secs = calc_time_distance(clock.unix_timestamp, expiry)?;
+
+        //Get asset price from oracle in usd, normalized to 10^8
+        let price = market.get_oracle_price(price_update, clock, market.max_price_age_buy_secs, false)?;
+
+        require!(!market.is_vol_stale(clock.unix_timestamp), CustomError::VolatilityStaled);
+
+        let strike_price_usd = select_strike(market, &price)?;
+        let pricing_spot_usd = buyer_spot_price(price.price as u128, price.conf as u128, &option)?;
+
+        let (collateral_usd, collateral_tokens) = calculate_collateral(
+            strike_price_usd,
+            pricing_spot_usd,
+            &option,
+            market,
+            time_to_expiry_secs,
+            quantity
+        )?;
+
+        let (premium_usd, premium_tokens, fee_tokens) = calculate_option_premium(
+            strike_price_usd,
+            pricing_spot_usd,
+            time_to_expiry_secs,
+            market,
+            &option,
+            quantity)?;
+
+        Ok(OptionQuote {
+            strike_price_usd: u64::try_from(strike_price_usd)?,
+            expiry,
+            spot_price_usd: price.price as u64,
+            premium_usd,
+            premium_tokens,
+            fee_tokens,
+            collateral_usd,
+            collateral_tokens
+        })
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(params: QuoteParams)]
+pub struct QuoteOption<'info> {
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+}
+
+impl QuoteOption<'_> {
+    //Read only, the quote is returned via return data so clients can get it by simulating the transaction
+    pub fn handle(ctx: Context<QuoteOption>, params: QuoteParams) -> Result<OptionQuote> {
+        let strike = params.strike;
+        let quote = OptionQuote::new(
+            &ctx.accounts.market,
+            &ctx.accounts.price_update,
+            &Clock::get()?,
+            params.option,
+            params.expiry_setting,
+            params.quantity,
+            |market, price| match strike {
+                StrikeSetting::SpotDeviation { deviation } => deviation.convert_to_strike(price.price as u128, market.strike_tick_size as u128),
+                StrikeSetting::Price { strike_price } => {
+                    market.validate_strike(strike_price as u128, price.price as u128)?;
+                    Ok(strike_price as u128)
+                }
+            })?;
+
+        msg!("Quote: strike {} expiry {} premium {} (usd {}) fee {} collateral {}",
+            quote.strike_price_usd,
+            quote.expiry,
+            quote.premium_tokens,
+            quote.premium_usd,
+            quote.fee_tokens,
+            quote.collateral_tokens);
+
+        Ok(quote)
+    }
+}
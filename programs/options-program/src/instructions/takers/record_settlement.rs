@@ -0,0 +1,88 @@
+use crate::common::ExerciseStyle;
+use crate::constants::SETTLEMENT_MAX_PUBLISH_DELAY_SECS;
+use crate::errors::*;
+use crate::state::event::*;
+use crate::state::market::*;
+use crate::state::settlement::*;
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RecordSettlementParams {
+    pub market_ix: u16,
+    pub expiry: i64
+}
+
+#[derive(Accounts)]
+#[instruction(params: RecordSettlementParams)]
+pub struct RecordSettlement<'info> {
+
+    // Permissionless, anyone can record the settlement price once the expiry is reached
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        init,
+        payer = signer,
+        seeds = [
+            SETTLEMENT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref(),
+            params.expiry.to_le_bytes().as_ref()
+        ],
+        bump,
+        space = 8 + Settlement::INIT_SPACE
+    )]
+    pub settlement: Account<'info, Settlement>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+    pub system_program: Program<'info, System>
+}
+
+impl RecordSettlement<'_> {
+    pub fn handle(ctx: Context<RecordSettlement>, params: RecordSettlementParams) -> Result<()> {
+        let market = &ctx.accounts.market;
+        require!(market.exercise_style == ExerciseStyle::European, CustomError::InvalidState);
+
+        let clock = Clock::get()?;
+        require!(clock.unix_timestamp >= params.expiry, CustomError::ExerciseTooEarly);
+        require!(clock.unix_timestamp <= params.expiry + market.exercise_window(), CustomError::ExerciseIsOverdue);
+
+        //The recorded price must be published at or right after expiry, so it can't be picked from a wider window.
+        //It can be recorded any time within the exercise window, so the age is only bounded by the expiry
+        let maximum_age = (clock.unix_timestamp - params.expiry) as u64;
+        let price = market.get_oracle_price(&ctx.accounts.price_update, &clock, maximum_age, market.exercise_use_ema)?;
+        require!(
+            params.expiry <= price.publish_time
+            && price.publish_time <= params.expiry + SETTLEMENT_MAX_PUBLISH_DELAY_SECS,
+            CustomError::InvalidPrices
+        );
+
+        let settlement = &mut ctx.accounts.settlement;
+        settlement.market_ix = params.market_ix;
+        settlement.expiry = params.expiry;
+        settlement.price = price.price as u64;
+        settlement.publish_time = price.publish_time;
+        settlement.bump = ctx.bumps.settlement;
+
+        msg!("Settlement price for market {} expiry {} recorded: {}", params.market_ix, params.expiry, settlement.price);
+
+        emit!(SettlementRecorded {
+            market: params.market_ix,
+            expiry: params.expiry,
+            price: settlement.price,
+            publish_time: settlement.publish_time,
+            recorded_by: ctx.accounts.signer.key()
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::common::*;
+use crate::errors::*;
+use crate::math::payout::calculate_sell_back_payout;
+use crate::state::event::*;
+use crate::state::user_account::*;
+use crate::state::market::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ self, Mint, TokenAccount, TokenInterface, TransferChecked };
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SellBackParams {
+    pub market_ix: u16,
+    pub page: u8,                   // Position page of the user account
+    pub option_id: u8,
+    pub quantity: u64,              // Units to sell back, the rest stays open
+    pub min_amount_out: u64
+}
+
+#[derive(Accounts)]
+#[instruction(params: SellBackParams)]
+pub struct SellBack<'info> {
+
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USR_ACC_SEED.as_bytes(),
+            signer.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
+        ],
+        bump
+    )]
+    pub account: AccountLoader<'info, UserAccount>,
+
+    #[account(
+        mut,
+        token::mint = asset_mint,
+        token::authority = signer
+    )]
+    pub user_token_acc: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_VAULT_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market_vault: InterfaceAccount<'info, TokenAccount>,
+
+    pub asset_mint: InterfaceAccount<'info, Mint>,
+    pub price_update: Account<'info, PriceUpdateV2>,
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl SellBack<'_> {
+    pub fn handle(ctx: Context<SellBack>, params: SellBackParams) -> Result<()> {
+        let user_account = &mut ctx.accounts.account.load_mut()?;
+        let market = &mut ctx.accounts.market;
+        require!(!market.is_paused(MARKET_PAUSE_SELL_BACK), CustomError::MarketPaused);
+
+        let slot = user_account.options.get_mut(params.option_id as usize).ok_or(CustomError::InvalidState)?;
+        require!(slot.is_initialized() && slot.market_ix == params.market_ix, CustomError::InvalidState);
+
+        //Options can only be sold back while they still have time value
+        let clock = Clock::get()?;
+        let time_to_expiry_secs = calc_time_distance(clock.unix_timestamp, slot.expiry)?;
+        require!(!market.is_vol_stale(clock.unix_timestamp), CustomError::VolatilityStaled);
+
+        //Priced at the side of the confidence interval that is worse for the holder
+        let price = market.get_oracle_price(&ctx.accounts.price_update, &clock, market.max_price_age_buy_secs, false)?;
+        let option_type = OptionType::try_from(slot.option_type).map_err(|_| CustomError::InvalidState)?;
+        let pricing_spot_usd = seller_spot_price(price.price as u128, price.conf as u128, &option_type)?;
+
+        //Sold part of the option, with its share of the collateral
+        let option = slot.take(params.quantity)?;
+        let remaining_quantity = slot.quantity;
+
+        let (value_usd, user_payout_in_tokens, spread_tokens) = calculate_sell_back_payout(&option, pricing_spot_usd, time_to_expiry_secs, market)?;
+        require!(user_payout_in_tokens >= params.min_amount_out, CustomError::SlippageExceeded);
+
+        if user_payout_in_tokens > 0 {
+            let market_ix_bytes = params.market_ix.to_le_bytes();
+
+            let signer_seeds: &[&[&[u8]]] = &[&[
+                MARKET_VAULT_SEED.as_bytes(),
+                market_ix_bytes.as_ref(),
+                &[ctx.bumps.market_vault]]];
+
+            token_interface::transfer_checked(
+                CpiContext::new(ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.market_vault.to_account_info(),
+                    to: ctx.accounts.user_token_acc.to_account_info(),
+                    authority: ctx.accounts.market_vault.to_account_info(),
+                    mint: ctx.accounts.asset_mint.to_account_info()
+                }).with_signer(signer_seeds),
+                user_payout_in_tokens,
+                ctx.accounts.asset_mint.decimals)?;
+
+            //Paid out of premiums first, the spread stays with LPs
+            market.pay_out(user_payout_in_tokens)?;
+        }
+
+        let released_collateral = option.max_potential_payout_in_tokens;
+        market.release_collateral(released_collateral)?;
+
+        msg!("User {} sold back {} of option {}, remaining {}", ctx.accounts.signer.key(), params.quantity, params.option_id, remaining_quantity);
+        msg!("Value usd (in 10^8) {}, payout tokens {}, spread tokens {}", value_usd, user_payout_in_tokens, spread_tokens);
+
+        emit!(OptionSoldBack {
+            user: ctx.accounts.signer.key(),
+            market: params.market_ix,
+            page: params.page,
+            option_ix: params.option_id,
+            option: option_type,
+            timestamp: clock.unix_timestamp,
+            quantity: params.quantity,
+            remaining_quantity,
+            value_usd,
+            user_payout: user_payout_in_tokens,
+            spread: spread_tokens,
+            released_collateral
+        });
+
+        Ok(())
+    }
+}
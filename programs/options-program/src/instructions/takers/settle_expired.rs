@@ -0,0 +1,77 @@
+use crate::common::OptionType;
+use crate::state::event::*;
+use crate::state::user_account::*;
+use crate::state::market::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettleExpiredParams {
+    pub market_ix: u16,
+    pub page: u8,                   // Position page of the user account
+}
+
+#[derive(Accounts)]
+#[instruction(params: SettleExpiredParams)]
+pub struct SettleExpired<'info> {
+
+    // Permissionless, anyone can release collateral of lapsed options
+    pub signer: Signer<'info>,
+
+    /// CHECK: Option holder wallet, only used to derive the user account
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            USR_ACC_SEED.as_bytes(),
+            owner.key().as_ref(),
+            UserAccount::page_seed(params.page).as_ref()
+        ],
+        bump
+    )]
+    pub account: AccountLoader<'info, UserAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+impl SettleExpired<'_> {
+    pub fn handle(ctx: Context<SettleExpired>, params: SettleExpiredParams) -> Result<()> {
+        let user_account = &mut ctx.accounts.account.load_mut()?;
+        let market = &mut ctx.accounts.market;
+        let stamp_now = Clock::get()?.unix_timestamp;
+        let exercise_window = market.exercise_window();
+
+        //Unexercised options past the market's exercise window expire worthless, their collateral goes back to LPs
+        for option in user_account.options.iter_mut()
+            .filter(|o| o.market_ix == params.market_ix && o.is_lapsed(stamp_now, exercise_window)) {
+
+            let released_collateral = option.max_potential_payout_in_tokens;
+            market.release_collateral(released_collateral)?;
+
+            msg!("Option {} of user {} expired. Released collateral: {}", option.ix, ctx.accounts.owner.key(), released_collateral);
+
+            emit!(OptionExpired {
+                user: ctx.accounts.owner.key(),
+                market: params.market_ix,
+                page: params.page,
+                option_ix: option.ix,
+                option: OptionType::try_from(option.option_type).unwrap(),
+                timestamp: stamp_now,
+                quantity: option.quantity,
+                released_collateral,
+            });
+
+            option.clear();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,74 @@
+use crate::common::OptionType;
+use crate::errors::*;
+use crate::state::event::*;
+use crate::state::market::*;
+use crate::state::option_series::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettleExpiredSeriesParams {
+    pub market_ix: u16,
+    pub option: OptionType,
+    pub strike_price: u64,
+    pub expiry: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(params: SettleExpiredSeriesParams)]
+pub struct SettleExpiredSeries<'info> {
+
+    // Permissionless, anyone can release collateral of unexercised option tokens
+    pub signer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [
+            OPTION_SERIES_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref(),
+            &[u8::from(params.option)],
+            params.strike_price.to_le_bytes().as_ref(),
+            params.expiry.to_le_bytes().as_ref()
+        ],
+        bump = option_series.bump,
+    )]
+    pub option_series: Account<'info, OptionSeries>,
+
+    #[account(
+        mut,
+        seeds = [
+            MARKET_SEED.as_bytes(),
+            params.market_ix.to_le_bytes().as_ref()
+        ],
+        bump,
+    )]
+    pub market: Account<'info, Market>,
+}
+
+impl SettleExpiredSeries<'_> {
+    pub fn handle(ctx: Context<SettleExpiredSeries>, _params: SettleExpiredSeriesParams) -> Result<()> {
+        let option_series = &mut ctx.accounts.option_series;
+        let market = &mut ctx.accounts.market;
+        let stamp_now = Clock::get()?.unix_timestamp;
+
+        //Option tokens left unexercised past the exercise window expire worthless, their collateral goes back to LPs
+        require!(option_series.as_option_order().is_lapsed(stamp_now, market.exercise_window()), CustomError::InvalidState);
+        //Already settled, or every token was exercised
+        require!(option_series.collateral > 0, CustomError::InvalidState);
+
+        let released_collateral = option_series.collateral;
+        market.release_collateral(released_collateral)?;
+        option_series.collateral = 0;
+
+        msg!("Option series {} expired. Unexercised supply: {}, released collateral: {}", option_series.key(), option_series.supply, released_collateral);
+
+        emit!(OptionSeriesExpired {
+            market: market.id,
+            series: option_series.key(),
+            timestamp: stamp_now,
+            unexercised_supply: option_series.supply,
+            released_collateral
+        });
+
+        Ok(())
+    }
+}
@@ -1,15 +1,15 @@
 use anchor_lang::prelude::*;
 
-mod state;
-mod errors;
-mod math;
-mod constants;
-mod instructions;
-mod common;
+pub mod state;
+pub mod errors;
+pub mod math;
+pub mod constants;
+pub mod instructions;
+pub mod common;
 
-use instructions::admin::{ market_create:: *, market_update_vol::*, withdraw_fees::*, market_close::* };
+use instructions::admin::{ protocol_initialize::*, admin_propose::*, admin_accept::*, role_grant::*, role_revoke::*, market_create:: *, market_update_vol::*, market_set_status::*, withdraw_fees::*, market_close::* };
 use instructions::makers::{ market_deposit::*, market_withdraw::* };
-use instructions::takers::{ acc_create::*, buy::*, exercise::* };
+use instructions::takers::{ acc_create::*, acc_close::*, buy::*, quote::*, exercise::*, exercise_for::*, settle_expired::*, record_settlement::*, sell_back::*, buy_tokenized::*, exercise_tokenized::*, settle_expired_series::* };
 
 // declare_id!("Be2AgTUf5uVfdHaSXPpzifVkmwfkgRwtLToVywevfvrS");
 declare_id!("3ZWb72v75w19dvHjwsqxe6gdK3yvU6p645PPEFpCSzHg");
@@ -19,6 +19,21 @@ pub mod options_program {
     use super::*;
 
     // --- Admin --- ///
+    pub fn initialize_protocol(ctx: Context<InitializeProtocol>, params: InitializeProtocolParams) -> Result<()> {
+        InitializeProtocol::handle(ctx, params)
+    }
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, params: ProposeAdminParams) -> Result<()> {
+        ProposeAdmin::handle(ctx, params)
+    }
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        AcceptAdmin::handle(ctx)
+    }
+    pub fn grant_role(ctx: Context<GrantRole>, params: GrantRoleParams) -> Result<()> {
+        GrantRole::handle(ctx, params)
+    }
+    pub fn revoke_role(ctx: Context<RevokeRole>, params: RevokeRoleParams) -> Result<()> {
+        RevokeRole::handle(ctx, params)
+    }
     pub fn create_market(ctx: Context<CreateMarket>, params: CreateMarketParams) -> Result<()> {
         CreateMarket::handle(ctx, params)
     }
@@ -31,19 +46,49 @@ pub mod options_program {
     pub fn close_market(ctx: Context<CloseMarket>, params: CloseMarketParams) -> Result<()> {
         CloseMarket::handle(ctx, params)
     }
-    //TODO:
-    //ix - Admin to pause market    
-    //ix - Instruction for off-chain service to exercise option on expiry on user's behalf (for convenience)
+    pub fn set_market_status(ctx: Context<SetMarketStatus>, params: SetMarketStatusParams) -> Result<()> {
+        SetMarketStatus::handle(ctx, params)
+    }
 
     // --- Takers (Option buyers) --- //
-    pub fn create_account(ctx: Context<AccountCreate>) -> Result<()> {
-        AccountCreate::handle(ctx)
+    pub fn create_account(ctx: Context<AccountCreate>, params: AccountCreateParams) -> Result<()> {
+        AccountCreate::handle(ctx, params)
+    }
+    pub fn close_account<'info>(ctx: Context<'_, '_, 'info, 'info, AccountClose<'info>>, params: AccountCloseParams) -> Result<()> {
+        AccountClose::handle(ctx, params)
     }
     pub fn buy(ctx: Context<BuyOption>, params: BuyOptionParams) -> Result<()> {
         BuyOption::handle(ctx, params)
     }
+    pub fn buy_at_strike(ctx: Context<BuyOption>, params: BuyOptionAtStrikeParams) -> Result<()> {
+        BuyOption::handle_at_strike(ctx, params)
+    }
+    pub fn quote_option(ctx: Context<QuoteOption>, params: QuoteParams) -> Result<OptionQuote> {
+        QuoteOption::handle(ctx, params)
+    }
+    pub fn buy_tokenized(ctx: Context<BuyTokenized>, params: BuyTokenizedParams) -> Result<()> {
+        BuyTokenized::handle(ctx, params)
+    }
     pub fn exercise(ctx: Context<ExerciseOption>, params: ExerciseOptionParams) -> Result<()> {
-        ExerciseOption::handle(ctx, params.market_ix, params.option_id)
+        ExerciseOption::handle(ctx, params.market_ix, params.page, params.option_id, params.quantity)
+    }
+    pub fn exercise_for(ctx: Context<ExerciseFor>, params: ExerciseForParams) -> Result<()> {
+        ExerciseFor::handle(ctx, params.market_ix, params.page, params.option_id)
+    }
+    pub fn sell_back(ctx: Context<SellBack>, params: SellBackParams) -> Result<()> {
+        SellBack::handle(ctx, params)
+    }
+    pub fn exercise_tokenized(ctx: Context<ExerciseTokenized>, params: ExerciseTokenizedParams) -> Result<()> {
+        ExerciseTokenized::handle(ctx, params)
+    }
+    pub fn settle_expired(ctx: Context<SettleExpired>, params: SettleExpiredParams) -> Result<()> {
+        SettleExpired::handle(ctx, params)
+    }
+    pub fn settle_expired_series(ctx: Context<SettleExpiredSeries>, params: SettleExpiredSeriesParams) -> Result<()> {
+        SettleExpiredSeries::handle(ctx, params)
+    }
+    pub fn record_settlement(ctx: Context<RecordSettlement>, params: RecordSettlementParams) -> Result<()> {
+        RecordSettlement::handle(ctx, params)
     }
 
     // --- Liquidity providers (LPs) --- //
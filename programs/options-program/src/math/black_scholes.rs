@@ -0,0 +1,149 @@
+use core::cmp::max;
+use anchor_lang::prelude::*;
+use crate::{common::OptionType, errors::CustomError, math::premium::sqrt};
+
+// Internal fixed-point scale (10^12). Inputs and outputs use the premium module's 10^8 precision
+const SCALE: i128 = 1_000_000_000_000;
+const INPUT_PRECISION: i128 = 100_000_000;
+
+const LN_2: i128 = 693_147_180_560;             // ln(2)
+const INV_SQRT_2PI: i128 = 398_942_280_401;     // 1 / sqrt(2 * pi)
+
+// Abramowitz & Stegun 26.2.17 coefficients, |error| < 7.5e-8
+const AS_P: i128 = 231_641_900_000;
+const AS_B1: i128 = 319_381_530_000;
+const AS_B2: i128 = -356_563_782_000;
+const AS_B3: i128 = 1_781_477_937_000;
+const AS_B4: i128 = -1_821_255_978_000;
+const AS_B5: i128 = 1_330_274_429_000;
+
+// N(x) is 0 or 1 within the approximation error beyond this bound
+const CDF_BOUND: i128 = 10 * SCALE;
+
+/// Black-Scholes premium with risk free rate assumed to be 0. Deterministic integer implementation.
+///
+/// @param spot_price - spot price in usd, scaled by 10^8
+///
+/// @param strike_price - strike price in usd, scaled by 10^8
+///
+/// @param time_to_expiry - in years, scaled by 10^8
+///
+/// @param volatility - annual volatility, scaled by 10^8
+///
+/// @returns Result<u64> - premium per unit in usd, scaled by 10^8
+pub fn calculate_premium(
+    spot_price: u128,
+    strike_price: u128,
+    time_to_expiry: u128,
+    volatility: u128,
+    option: &OptionType
+) -> Result<u64> {
+    require!(spot_price > 0, CustomError::InvalidSpotPrice);
+    require!(strike_price > 0, CustomError::InvalidStrikePrice);
+
+    let s = i128::try_from(spot_price).map_err(|_| CustomError::Overflow)?;
+    let k = i128::try_from(strike_price).map_err(|_| CustomError::Overflow)?;
+    let t = i128::try_from(time_to_expiry).map_err(|_| CustomError::Overflow)? * (SCALE / INPUT_PRECISION);
+    let sigma = i128::try_from(volatility).map_err(|_| CustomError::Overflow)? * (SCALE / INPUT_PRECISION);
+    require!(t > 0 && sigma > 0, CustomError::PremiumCalcError);
+
+    // sigma * sqrt(t)
+    let sqrt_t = sqrt((t * SCALE) as u128) as i128;
+    let sigma_sqrt_t = sigma * sqrt_t / SCALE;
+    require!(sigma_sqrt_t > 0, CustomError::PremiumCalcError);
+
+    // d1 = (ln(S/K) + sigma^2 * t / 2) / (sigma * sqrt(t)), d2 = d1 - sigma * sqrt(t)
+    let ln_s_k = ln_fixed(s * SCALE / k)?;
+    let half_variance = sigma_sqrt_t * sigma_sqrt_t / SCALE / 2;
+    let d1 = (ln_s_k + half_variance) * SCALE / sigma_sqrt_t;
+    let d2 = d1 - sigma_sqrt_t;
+
+    let premium = match option {
+        OptionType::CALL => s * norm_cdf(d1)? / SCALE - k * norm_cdf(d2)? / SCALE,
+        OptionType::PUT => k * norm_cdf(-d2)? / SCALE - s * norm_cdf(-d1)? / SCALE,
+    };
+
+    u64::try_from(max(premium, 0)).map_err(|_| CustomError::Overflow.into())
+}
+
+// Cumulative distribution function of the standard normal distribution (A&S 26.2.17)
+fn norm_cdf(x: i128) -> Result<i128> {
+    if x >= CDF_BOUND {
+        return Ok(SCALE);
+    }
+    if x <= -CDF_BOUND {
+        return Ok(0);
+    }
+
+    let abs_x = x.abs();
+    let t = SCALE * SCALE / (SCALE + AS_P * abs_x / SCALE);
+
+    // Horner's scheme: t * (b1 + t * (b2 + t * (b3 + t * (b4 + t * b5))))
+    let mut poly = AS_B5;
+    for b in [AS_B4, AS_B3, AS_B2, AS_B1] {
+        poly = b + t * poly / SCALE;
+    }
+    poly = t * poly / SCALE;
+
+    let pdf = INV_SQRT_2PI * exp_fixed(-(abs_x * abs_x / SCALE) / 2)? / SCALE;
+    let upper = SCALE - pdf * poly / SCALE;
+
+    Ok(if x >= 0 { upper } else { SCALE - upper })
+}
+
+// Natural logarithm, x > 0 scaled by SCALE
+fn ln_fixed(x: i128) -> Result<i128> {
+    require!(x > 0, CustomError::PremiumCalcError);
+
+    // Range reduction: x = m * 2^k, m in [1, 2)
+    let mut m = x;
+    let mut k: i128 = 0;
+    while m >= 2 * SCALE {
+        m /= 2;
+        k += 1;
+    }
+    while m < SCALE {
+        m *= 2;
+        k -= 1;
+    }
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3/3 + z^5/5 + ...), z = (m - 1) / (m + 1) <= 1/3
+    let z = (m - SCALE) * SCALE / (m + SCALE);
+    let z2 = z * z / SCALE;
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z2 / SCALE;
+        n += 2;
+    }
+
+    Ok(k * LN_2 + 2 * sum)
+}
+
+// e^x, x scaled by SCALE
+fn exp_fixed(x: i128) -> Result<i128> {
+    // Range reduction: x = k * ln(2) + r, r in [0, ln(2))
+    let k = x.div_euclid(LN_2);
+    let r = x.rem_euclid(LN_2);
+
+    // Taylor series for e^r
+    let mut term = SCALE;
+    let mut sum = SCALE;
+    let mut n = 1;
+    while term != 0 {
+        term = term * r / SCALE / n;
+        sum += term;
+        n += 1;
+    }
+
+    if k >= 0 {
+        require!(k < 80, CustomError::Overflow);
+        Ok(sum << k)
+    } else if k > -100 {
+        Ok(sum >> -k)
+    } else {
+        Ok(0)
+    }
+}
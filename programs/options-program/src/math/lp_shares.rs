@@ -17,22 +17,22 @@ pub fn calc_lp_shares(base_asset_amount: u64, min_amount_out: u64, market: &Mark
     require!(base_asset_amount > 0, CustomError::InvalidAmount);
     require!(min_amount_out > 0, CustomError::InvalidAmount);
 
-    let market_tvl = market.premiums.checked_add(market.reserve_supply).unwrap();
+    let market_tvl = market.premiums.checked_add(market.reserve_supply).ok_or(CustomError::Overflow)?;
 
     let lp_tokens_to_mint = if market.lp_minted == 0 {
-        base_asset_amount * 1_000
+        base_asset_amount.checked_mul(1_000).ok_or(CustomError::Overflow)?
     } else {
+        //Outstanding shares of a pool that was paid out entirely can't be priced
+        require!(market_tvl > 0, CustomError::InvalidState);
         let scale = 1_000_000_000 as u64;
 
         let scaled_asset = (base_asset_amount as u128)
-            .checked_mul(scale as u128).unwrap()
-            .checked_div(market_tvl as u128).unwrap();
-        // println!("scaled asset {:?}", scaled_asset);
+            .checked_mul(scale as u128).ok_or(CustomError::Overflow)?
+            .checked_div(market_tvl as u128).ok_or(CustomError::Overflow)?;
 
         let lp_tokens = scaled_asset
-            .checked_mul(market.lp_minted as u128).unwrap()
-            .checked_div(scale as u128).unwrap();
-        // println!("lp_tokens {:?}", lp_tokens);
+            .checked_mul(market.lp_minted as u128).ok_or(CustomError::Overflow)?
+            .checked_div(scale as u128).ok_or(CustomError::Overflow)?;
 
         let lp_tokens_u64 = lp_tokens.try_into().map_err(|_| CustomError::Overflow)?;
 
@@ -57,28 +57,29 @@ pub fn calc_withdraw_amount_from_lp_shares(lp_tokens_to_burn: u64, market: &Mark
     let scale = 1_000_000_000 as u64;
 
     let ownership_ratio = (lp_tokens_to_burn as u128)
-        .checked_mul(scale as u128).unwrap()
-        .checked_div(market.lp_minted as u128).unwrap();
+        .checked_mul(scale as u128).ok_or(CustomError::Overflow)?
+        .checked_div(market.lp_minted as u128).ok_or(CustomError::Overflow)?;
 
     let market_tvl = market.reserve_supply
-        .checked_add(market.premiums).unwrap();
+        .checked_add(market.premiums).ok_or(CustomError::Overflow)?;
     require!(market_tvl > 0, CustomError::InvalidState);
 
     let potential_withdraw_amount = ownership_ratio
-        .checked_mul(market_tvl as u128).unwrap()
-        .checked_div(scale as u128).unwrap() as u64;
+        .checked_mul(market_tvl as u128).ok_or(CustomError::Overflow)?
+        .checked_div(scale as u128).ok_or(CustomError::Overflow)? as u64;
 
     //Check if amount to be withdraw is not as collateral to unexercised options
     let uncomitted_reserve = market_tvl
-        .checked_sub(market.committed_reserve).unwrap();
+        .checked_sub(market.committed_reserve).ok_or(CustomError::InvalidState)?;
        
     let withdrawable_amount = min(uncomitted_reserve, potential_withdraw_amount);
     require!(withdrawable_amount >= 1, CustomError::CannotWithdraw);
 
+    //Rounded up, so a capped withdrawal never burns less than the value it takes out
     let actual_lp_tokens_to_burn = if withdrawable_amount < potential_withdraw_amount {
         ((withdrawable_amount as u128)
-            .checked_mul(market.lp_minted as u128).unwrap()
-            .checked_div(market_tvl as u128).unwrap()
+            .checked_mul(market.lp_minted as u128).ok_or(CustomError::Overflow)?
+            .div_ceil(market_tvl as u128)
         ) as u64
     } else {
         lp_tokens_to_burn
@@ -86,8 +87,29 @@ pub fn calc_withdraw_amount_from_lp_shares(lp_tokens_to_burn: u64, market: &Mark
 
     require!(actual_lp_tokens_to_burn > 0, CustomError::InvalidAmount);
 
-    msg!("Requested tokens to burn: {}, max withdrawable: {}, actual tokens to burn: {}",
-        lp_tokens_to_burn, withdrawable_amount, actual_lp_tokens_to_burn);
-
     Ok((withdrawable_amount, actual_lp_tokens_to_burn))
+}
+
+/// Splits a withdrawal between LP reserve and accrued premiums. The reserve part is proportional to
+/// the uncommitted reserve's share of market value and premiums cover the rest, up to their balance.
+/// The remainder comes out of the uncommitted reserve, so collateral of open options stays in the pool.
+/// 
+/// @returns Result<(u64, u64)> - Reserve and premium parts of the withdrawal
+pub fn split_withdraw_amount(withdraw_amount: u64, market: &Market) -> Result<(u64, u64)> {
+    let market_tvl = market.reserve_supply
+        .checked_add(market.premiums).ok_or(CustomError::Overflow)?;
+    require!(market_tvl > 0, CustomError::InvalidState);
+
+    let uncomitted_reserve = market.reserve_supply
+        .checked_sub(market.committed_reserve).ok_or(CustomError::InvalidState)?;
+
+    let proportional_reserve_share = (withdraw_amount as u128)
+        .checked_mul(uncomitted_reserve as u128).ok_or(CustomError::Overflow)?
+        .checked_div(market_tvl as u128).ok_or(CustomError::Overflow)? as u64;
+
+    let premium_share = min(withdraw_amount - proportional_reserve_share, market.premiums);
+    let reserve_share = withdraw_amount - premium_share;
+    require!(reserve_share <= uncomitted_reserve, CustomError::CannotWithdraw);
+
+    Ok((reserve_share, premium_share))
 }
\ No newline at end of file
@@ -1,2 +1,4 @@
 pub mod premium;
-pub mod lp_shares;
\ No newline at end of file
+pub mod black_scholes;
+pub mod lp_shares;
+pub mod payout;
\ No newline at end of file
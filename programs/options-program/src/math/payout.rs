@@ -0,0 +1,75 @@
+use core::cmp::min;
+use anchor_lang::prelude::*;
+use crate::{common::OptionType, constants::BASIS_POINTS_DENOMINATOR, errors::CustomError, math::premium::calculate_option_value, state::{market::Market, user_account::OptionOrder}};
+
+/// Calculates the payout of an option exercised at the given spot price.
+/// The payout is capped at the collateral locked for the option, to keep the market solvent.
+///
+/// @param option - Option being exercised
+///
+/// @param spot_price_usd - Current asset price in usd, scaled by 10^8 (Pyth)
+///
+/// @param market - Market the option was bought in, for its contract size
+///
+/// @returns Result<(u64, u64)> - (profit in usd scaled by 10^8, payout in token base units)
+pub fn calculate_exercise_payout(option: &OptionOrder, spot_price_usd: u64, market: &Market) -> Result<(u64, u64)> {
+    require!(spot_price_usd > 0, CustomError::InvalidSpotPrice);
+
+    let option_type = OptionType::try_from(option.option_type)
+        .map_err(|_| CustomError::InvalidState)?;
+
+    //Profit per whole asset unit
+    let unit_profit_usd = match option_type {
+        OptionType::CALL => spot_price_usd.saturating_sub(option.strike_price),
+        OptionType::PUT => option.strike_price.saturating_sub(spot_price_usd)
+    } as u128;
+
+    if unit_profit_usd == 0 {
+        return Ok((0, 0));
+    }
+
+    let profit_usd = u64::try_from(market.contracts_value_usd(unit_profit_usd, option.quantity)?)
+        .map_err(|_| CustomError::Overflow)?;
+    let profit_in_tokens = u64::try_from(market.contracts_value_tokens(unit_profit_usd, option.quantity, spot_price_usd as u128)?)
+        .map_err(|_| CustomError::Overflow)?;
+
+    //There is limit to payouts for solvency
+    let payout_in_tokens = min(profit_in_tokens, option.max_potential_payout_in_tokens);
+
+    Ok((profit_usd, payout_in_tokens))
+}
+
+/// Calculates the amount paid to a holder selling an open option back to the pool.
+/// The option is priced with the market premium model on the time remaining, minus the market sell back spread,
+/// which stays with LPs. The payout is capped at the collateral locked for the option.
+///
+/// @param option - Option being sold back
+///
+/// @param spot_price_usd - Current asset price in usd, scaled by 10^8
+///
+/// @param time_to_expiry_secs - Seconds left until the option expires
+///
+/// @param market - Market the option was bought in
+///
+/// @returns Result<(u64, u64, u64)> - (model value in usd scaled by 10^8, payout in token base units, spread in token base units)
+pub fn calculate_sell_back_payout(option: &OptionOrder, spot_price_usd: u128, time_to_expiry_secs: u64, market: &Market) -> Result<(u64, u64, u64)> {
+    let option_type = OptionType::try_from(option.option_type)
+        .map_err(|_| CustomError::InvalidState)?;
+
+    //Worthless options close for nothing, their collateral still goes back to LPs
+    let (value_usd, value_tokens) = calculate_option_value(
+        option.strike_price as u128,
+        spot_price_usd,
+        time_to_expiry_secs,
+        market,
+        &option_type,
+        option.quantity
+    )?;
+
+    let spread_tokens = value_tokens
+        .checked_mul(market.sell_back_spread_bps).ok_or(CustomError::Overflow)?
+        .checked_div(BASIS_POINTS_DENOMINATOR).ok_or(CustomError::Overflow)?;
+    let payout_in_tokens = min(value_tokens - spread_tokens, option.max_potential_payout_in_tokens);
+
+    Ok((value_usd, payout_in_tokens, value_tokens - payout_in_tokens))
+}
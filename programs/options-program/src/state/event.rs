@@ -17,10 +17,11 @@ pub struct MakerDepositEvent {
 pub struct OptionBought {
     pub user: Pubkey,
     pub market: u16,
+    pub page: u8,                       // Position page of the user account
     pub option_ix: u8,
     pub option: OptionType,
-    pub strike_price_usd: u64, //strike price in usd scaled by 6 decimals 
-    pub bought_at_price_usd: u64, //bought price in usd scaled by 6 decimals 
+    pub strike_price_usd: u64, //strike price in usd scaled by 8 decimals 
+    pub bought_at_price_usd: u64, //bought price in usd scaled by 8 decimals 
     pub max_potential_payout_in_tokens: u64,
     pub expiry_stamp: i64,
     // pub created_stamp: i64,
@@ -30,13 +31,90 @@ pub struct OptionBought {
 #[event]
 pub struct OptionExercised {
     pub user: Pubkey,
+    pub exercised_by: Pubkey,           // Holder, or keeper when exercised via exercise_for
     pub market: u16,
+    pub page: u8,
     pub option_ix: u8,
     pub option: OptionType,
     pub timestamp: i64,
     pub quantity: u64,
+    pub remaining_quantity: u64,        // Left open in the slot after a partial exercise
     pub profit_usd: u64, 
     pub user_payout: u64, 
+    pub keeper_bounty: u64,
+}
+
+#[event]
+pub struct OptionExpired {
+    pub user: Pubkey,
+    pub market: u16,
+    pub page: u8,
+    pub option_ix: u8,
+    pub option: OptionType,
+    pub timestamp: i64,
+    pub quantity: u64,
+    pub released_collateral: u64,
+}
+
+#[event]
+pub struct OptionSoldBack {
+    pub user: Pubkey,
+    pub market: u16,
+    pub page: u8,
+    pub option_ix: u8,
+    pub option: OptionType,
+    pub timestamp: i64,
+    pub quantity: u64,
+    pub remaining_quantity: u64,
+    pub value_usd: u64,                 // Model value of the option, usd scaled by 10^8
+    pub user_payout: u64,
+    pub spread: u64,                    // Tokens kept by LPs
+    pub released_collateral: u64,
+}
+
+#[event]
+pub struct OptionTokensBought {
+    pub user: Pubkey,
+    pub market: u16,
+    pub series: Pubkey,
+    pub mint: Pubkey,
+    pub option: OptionType,
+    pub strike_price_usd: u64,
+    pub bought_at_price_usd: u64,
+    pub expiry_stamp: i64,
+    pub quantity: u64,
+    pub premium: u64,
+    pub max_potential_payout_in_tokens: u64,
+}
+
+#[event]
+pub struct OptionTokensExercised {
+    pub user: Pubkey,
+    pub market: u16,
+    pub series: Pubkey,
+    pub option: OptionType,
+    pub timestamp: i64,
+    pub quantity: u64,                  // Option tokens burned
+    pub profit_usd: u64,
+    pub user_payout: u64,
+}
+
+#[event]
+pub struct OptionSeriesExpired {
+    pub market: u16,
+    pub series: Pubkey,
+    pub timestamp: i64,
+    pub unexercised_supply: u64,
+    pub released_collateral: u64,
+}
+
+#[event]
+pub struct SettlementRecorded {
+    pub market: u16,
+    pub expiry: i64,
+    pub price: u64,
+    pub publish_time: i64,
+    pub recorded_by: Pubkey,
 }
 
 #[event]
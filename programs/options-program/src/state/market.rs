@@ -1,11 +1,32 @@
 use anchor_lang::prelude::*;
-use crate::{common::*};
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, Price, PriceUpdateV2};
+use crate::{common::*, constants::{BASIS_POINTS_DENOMINATOR, EUROPEAN_EXERCISE_WINDOW_SECS, EXERCISE_INTERVAL_TOLERANCE, PRICE_DECIMALS}, errors::CustomError, math::lp_shares::*, state::settlement::Settlement};
 
 pub const MARKET_SEED: &str = "market";
 pub const MARKET_VAULT_SEED: &str = "market_vault";
 pub const PROTOCOL_FEES_VAULT_SEED: &str = "protocol_fees_vault";
 pub const MARKET_LP_MINT_SEED: &str = "market_lp_mint";
 
+//Volatility surface dimensions: expiry buckets x strike nodes
+pub const VOL_SURFACE_EXPIRIES: usize = 5;          // Expiry::HOUR1 .. Expiry::WEEK
+pub const VOL_SURFACE_STRIKES: usize = 9;           // SpotDeviation::N20 .. SpotDeviation::P20
+pub const VOL_SURFACE_MIN_MONEYNESS_BPS: u128 = 8_000;  // Strike node 0 at 80% of spot
+pub const VOL_SURFACE_STEP_BPS: u128 = 500;             // Strike nodes 5% of spot apart
+
+//Market status flags. A set bit pauses the operation
+pub const MARKET_PAUSE_BUY: u8 = 1 << 0;
+pub const MARKET_PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const MARKET_PAUSE_WITHDRAW: u8 = 1 << 2;
+pub const MARKET_PAUSE_EXERCISE: u8 = 1 << 3;
+pub const MARKET_PAUSE_SELL_BACK: u8 = 1 << 4;
+pub const MARKET_PAUSE_ALL: u8 = MARKET_PAUSE_BUY | MARKET_PAUSE_DEPOSIT | MARKET_PAUSE_WITHDRAW | MARKET_PAUSE_EXERCISE | MARKET_PAUSE_SELL_BACK;
+
+//Expiry series flags. A set bit lists the series for the market
+pub const EXPIRY_SERIES_DAILY: u8 = 1 << 0;
+pub const EXPIRY_SERIES_WEEKLY: u8 = 1 << 1;
+pub const EXPIRY_SERIES_CUSTOM: u8 = 1 << 2;
+pub const EXPIRY_SERIES_ALL: u8 = EXPIRY_SERIES_DAILY | EXPIRY_SERIES_WEEKLY | EXPIRY_SERIES_CUSTOM;
+
 #[account]
 #[derive(InitSpace, PartialEq, Eq)]
 pub struct Market {
@@ -22,35 +43,293 @@ pub struct Market {
     #[max_len(70)]
     pub price_feed: String,             // Pyth feed (TOKEN)/USD
     pub asset_decimals: u8,
-    pub hour1_volatility_bps: u32,      // 1bps = 0.01%
-    pub hour4_volatility_bps: u32,  
-    pub day1_volatility_bps: u32,  
-    pub day3_volatility_bps: u32,  
-    pub week_volatility_bps: u32,
-    pub vol_last_updated: i64  
+    pub vol_surface_bps: [[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES], // [expiry][strike], 1bps = 0.01%
+    pub vol_last_updated: i64,
+    pub max_vol_age_secs: u64,          // Buys are rejected when the surface is older than this
+    pub min_volatility_bps: u32,        // Bounds for every surface node
+    pub max_volatility_bps: u32,
+    pub max_vol_change_bps: u32,        // Max change of a surface node per update, 0 = no limit
+    pub status: u8,                     // Bitfield of MARKET_PAUSE_* flags
+    pub keeper_bounty_bps: u64,         // Share of the payout paid to keepers exercising on the holder's behalf
+    pub pricing_model: PricingModel,
+    pub max_price_age_buy_secs: u64,    // Oracle price max age when pricing new options
+    pub max_price_age_exercise_secs: u64,
+    pub max_price_conf_bps: u64,        // Max oracle confidence interval relative to price
+    pub exercise_use_ema: bool,         // Settle exercises at the EMA price to blunt spot manipulation
+    pub strike_tick_size: u64,          // Strikes are rounded to multiples of this, usd scaled by 10^8
+    pub min_strike_moneyness_bps: u32,  // Band for user chosen strikes, strike / spot. 8000 = 80% of spot
+    pub max_strike_moneyness_bps: u32,
+    pub expiry_series: u8,              // Bitfield of EXPIRY_SERIES_* flags
+    pub expiry_hour_utc: u8,            // Daily and weekly series expire at this hour, e.g. 8 = 08:00 UTC
+    pub min_time_to_expiry_secs: u64,   // Series roll to the next expiry when the current one is closer than this
+    pub exercise_style: ExerciseStyle,
+    pub sell_back_spread_bps: u64,      // Discount off the model value when holders sell options back, kept by LPs
+    pub contract_size: u64,             // Asset base units per unit of option quantity, e.g. 10^8 = 0.1 SOL
 }
 
 impl Market {
-    pub fn get_volatility(&self, expiry_setting: &Expiry) -> Result<u32> {
-        //Expiry to be measured as distance in seconds
-        match expiry_setting {
-            Expiry::HOUR1 => {
-                Ok(self.hour1_volatility_bps)
-            },
-            Expiry::HOUR4 => {
-                Ok(self.hour4_volatility_bps)
-            },
-            Expiry::DAY1 => {
-                Ok(self.day1_volatility_bps)
-            },
-            Expiry::DAY3 => {
-                Ok(self.day3_volatility_bps)
-            },
-            Expiry::WEEK => {
-                Ok(self.week_volatility_bps)
+    pub fn is_paused(&self, operation: u8) -> bool {
+        self.status & operation != 0
+    }
+
+    //Adds a deposit to the LP reserve, returns the LP tokens to mint
+    pub fn add_liquidity(&mut self, amount: u64, min_amount_out: u64) -> Result<u64> {
+        let lp_tokens_to_mint = calc_lp_shares(amount, min_amount_out, self)?;
+
+        self.lp_minted = self.lp_minted
+            .checked_add(lp_tokens_to_mint)
+            .ok_or(CustomError::Overflow)?;
+        self.reserve_supply = self.reserve_supply
+            .checked_add(amount)
+            .ok_or(CustomError::Overflow)?;
+
+        Ok(lp_tokens_to_mint)
+    }
+
+    //Redeems LP tokens, capped by uncommitted value. Returns the tokens to pay out and the LP tokens to burn
+    pub fn remove_liquidity(&mut self, lp_tokens_to_burn: u64, min_amount_out: u64) -> Result<(u64, u64)> {
+        let (withdraw_amount, lp_tokens_burned) = calc_withdraw_amount_from_lp_shares(lp_tokens_to_burn, self)?;
+        require!(withdraw_amount >= min_amount_out, CustomError::SlippageExceeded);
+
+        let (reserve_share, premium_share) = split_withdraw_amount(withdraw_amount, self)?;
+        self.reserve_supply = self.reserve_supply
+            .checked_sub(reserve_share)
+            .ok_or(CustomError::Overflow)?;
+        self.premiums = self.premiums
+            .checked_sub(premium_share)
+            .ok_or(CustomError::Overflow)?;
+        self.lp_minted = self.lp_minted
+            .checked_sub(lp_tokens_burned)
+            .ok_or(CustomError::Overflow)?;
+
+        Ok((withdraw_amount, lp_tokens_burned))
+    }
+
+    //Premium net of protocol fees goes to LPs, the option's max payout is locked in the reserve
+    pub fn accrue_premium(&mut self, lp_share: u64, collateral: u64) -> Result<()> {
+        self.premiums = self.premiums
+            .checked_add(lp_share)
+            .ok_or(CustomError::Overflow)?;
+        self.committed_reserve = self.committed_reserve
+            .checked_add(collateral)
+            .ok_or(CustomError::Overflow)?;
+
+        Ok(())
+    }
+
+    //Payouts are covered by accrued premiums first, the remainder comes out of LP reserve
+    pub fn pay_out(&mut self, amount: u64) -> Result<()> {
+        if amount <= self.premiums {
+            self.premiums = self.premiums
+                .checked_sub(amount)
+                .ok_or(CustomError::Overflow)?;
+        } else {
+            let remainder = amount - self.premiums;
+            self.premiums = 0;
+            self.reserve_supply = self.reserve_supply
+                .checked_sub(remainder)
+                .ok_or(CustomError::Overflow)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn release_collateral(&mut self, amount: u64) -> Result<()> {
+        self.committed_reserve = self.committed_reserve
+            .checked_sub(amount)
+            .ok_or(CustomError::Overflow)?;
+
+        Ok(())
+    }
+
+    //Usd value of `quantity` contracts, from a usd value per whole asset unit (both scaled by 10^8)
+    pub fn contracts_value_usd(&self, value_per_asset_usd: u128, quantity: u64) -> Result<u128> {
+        Ok(value_per_asset_usd
+            .checked_mul(quantity as u128).ok_or(CustomError::Overflow)?
+            .checked_mul(self.contract_size as u128).ok_or(CustomError::Overflow)?
+            .checked_div(10_u128.pow(self.asset_decimals as u32)).ok_or(CustomError::Overflow)?)
+    }
+
+    //Same value in asset base units at the spot price, computed without rounding the usd value first
+    pub fn contracts_value_tokens(&self, value_per_asset_usd: u128, quantity: u64, spot_price_usd: u128) -> Result<u128> {
+        require!(spot_price_usd > 0, CustomError::InvalidSpotPrice);
+
+        Ok(value_per_asset_usd
+            .checked_mul(quantity as u128).ok_or(CustomError::Overflow)?
+            .checked_mul(self.contract_size as u128).ok_or(CustomError::Overflow)?
+            .checked_div(spot_price_usd).ok_or(CustomError::Overflow)?)
+    }
+
+    //Volatility for the time to expiry and strike. Linearly interpolated between the surface tenors by time,
+    //and between strike nodes by moneyness (strike / spot). Points outside of the surface use the edge nodes.
+    pub fn get_volatility(&self, time_to_expiry_secs: u64, strike_price: u128, spot_price: u128) -> Result<u32> {
+        require!(spot_price > 0, CustomError::InvalidSpotPrice);
+
+        let tenor_secs = |row: usize| -> Result<u64> {
+            Expiry::try_from(row as u8)
+                .and_then(|expiry| expiry.to_seconds())
+                .map_err(|_| CustomError::InvalidExpiry.into())
+        };
+
+        let last_row = VOL_SURFACE_EXPIRIES - 1;
+        if time_to_expiry_secs <= tenor_secs(0)? {
+            return self.get_strike_volatility(0, strike_price, spot_price);
+        }
+        if time_to_expiry_secs >= tenor_secs(last_row)? {
+            return self.get_strike_volatility(last_row, strike_price, spot_price);
+        }
+
+        let mut row = 0;
+        while time_to_expiry_secs >= tenor_secs(row + 1)? {
+            row += 1;
+        }
+
+        let lower_secs = tenor_secs(row)? as i128;
+        let upper_secs = tenor_secs(row + 1)? as i128;
+        let lower = self.get_strike_volatility(row, strike_price, spot_price)? as i128;
+        let upper = self.get_strike_volatility(row + 1, strike_price, spot_price)? as i128;
+        let vol = lower + (upper - lower) * (time_to_expiry_secs as i128 - lower_secs) / (upper_secs - lower_secs);
+
+        Ok(vol as u32)
+    }
+
+    //Volatility of a surface row, interpolated between strike nodes. Strikes outside of the 80% - 120% band use the edge node.
+    fn get_strike_volatility(&self, row: usize, strike_price: u128, spot_price: u128) -> Result<u32> {
+        let row = &self.vol_surface_bps[row];
+
+        let max_moneyness_bps = VOL_SURFACE_MIN_MONEYNESS_BPS + VOL_SURFACE_STEP_BPS * (VOL_SURFACE_STRIKES as u128 - 1);
+        let moneyness_bps = strike_price
+            .checked_mul(10_000).ok_or(CustomError::Overflow)?
+            .checked_div(spot_price).ok_or(CustomError::Overflow)?
+            .clamp(VOL_SURFACE_MIN_MONEYNESS_BPS, max_moneyness_bps);
+
+        let offset = moneyness_bps - VOL_SURFACE_MIN_MONEYNESS_BPS;
+        let node = (offset / VOL_SURFACE_STEP_BPS) as usize;
+        if node == VOL_SURFACE_STRIKES - 1 {
+            return Ok(row[node]);
+        }
+
+        let lower = row[node] as i128;
+        let upper = row[node + 1] as i128;
+        let weight = (offset % VOL_SURFACE_STEP_BPS) as i128;
+        let vol = lower + (upper - lower) * weight / VOL_SURFACE_STEP_BPS as i128;
+
+        Ok(vol as u32)
+    }
+
+    pub fn validate_vol_surface(&self, vol_surface_bps: &[[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES]) -> Result<()> {
+        require!(
+            vol_surface_bps.iter().flatten().all(|v| (self.min_volatility_bps..=self.max_volatility_bps).contains(v)),
+            CustomError::InvalidVolatility
+        );
+
+        Ok(())
+    }
+
+    //Guards against a faulty vol keeper pushing absurd jumps
+    pub fn validate_vol_change(&self, vol_surface_bps: &[[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES]) -> Result<()> {
+        if self.max_vol_change_bps == 0 {
+            return Ok(());
+        }
+
+        require!(
+            self.vol_surface_bps.iter().flatten()
+                .zip(vol_surface_bps.iter().flatten())
+                .all(|(current, new)| current.abs_diff(*new) <= self.max_vol_change_bps),
+            CustomError::VolatilityChangeTooLarge
+        );
+
+        Ok(())
+    }
+
+    //Reads the market feed from the oracle and rejects stale, non-positive or too uncertain prices.
+    //Price and confidence are returned in usd, scaled by 10^PRICE_DECIMALS
+    pub fn get_oracle_price(&self, price_update: &PriceUpdateV2, clock: &Clock, maximum_age: u64, use_ema: bool) -> Result<Price> {
+        let feed_id = get_feed_id_from_hex(self.price_feed.as_str())?;
+        let mut price = price_update.get_price_no_older_than(clock, maximum_age, &feed_id)?;
+
+        if use_ema {
+            price.price = price_update.price_message.ema_price;
+            price.conf = price_update.price_message.ema_conf;
+        }
+
+        require!(price.price > 0, CustomError::InvalidSpotPrice);
+
+        //Normalize to PRICE_DECIMALS, so feeds with any exponent price and settle the same way
+        price.price = normalize_price(price.price as u64, price.exponent)? as i64;
+        price.conf = normalize_price(price.conf, price.exponent)?;
+        price.exponent = -PRICE_DECIMALS;
+        require!(price.price > 0, CustomError::InvalidSpotPrice);
+
+        let conf_bps = (price.conf as u128)
+            .checked_mul(BASIS_POINTS_DENOMINATOR as u128).ok_or(CustomError::Overflow)?
+            .checked_div(price.price as u128).ok_or(CustomError::Overflow)?;
+        require!(conf_bps <= self.max_price_conf_bps as u128, CustomError::PriceConfidenceTooWide);
+
+        Ok(price)
+    }
+
+    //How long after expiry options can still be exercised. American options are exercised at the current price,
+    //so their window is short. European ones all settle at the recorded price, which doesn't move
+    pub fn exercise_window(&self) -> i64 {
+        match self.exercise_style {
+            ExerciseStyle::American => EXERCISE_INTERVAL_TOLERANCE,
+            ExerciseStyle::European => EUROPEAN_EXERCISE_WINDOW_SECS,
+        }
+    }
+
+    //Price options are exercised at. American options use the current oracle price,
+    //European ones the settlement price recorded for their expiry
+    pub fn get_exercise_price(&self, option_expiry: i64, price_update: &PriceUpdateV2, settlement: Option<&Settlement>, clock: &Clock) -> Result<u64> {
+        match self.exercise_style {
+            ExerciseStyle::American => {
+                let price = self.get_oracle_price(price_update, clock, self.max_price_age_exercise_secs, self.exercise_use_ema)?;
+                Ok(price.price as u64)
             },
+            ExerciseStyle::European => {
+                require!(clock.unix_timestamp >= option_expiry, CustomError::ExerciseTooEarly);
+                let settlement = settlement.ok_or(CustomError::SettlementNotRecorded)?;
+                require!(
+                    settlement.market_ix == self.id && settlement.expiry == option_expiry,
+                    CustomError::SettlementNotRecorded
+                );
+                Ok(settlement.price)
+            }
         }
     }
 
-    
+    pub fn is_vol_stale(&self, stamp_now: i64) -> bool {
+        stamp_now.saturating_sub(self.vol_last_updated) > self.max_vol_age_secs as i64
+    }
+
+    //Expiry timestamp for the buyer's setting, if the market lists that series
+    pub fn get_expiry_stamp(&self, expiry_setting: &ExpirySetting, stamp_now: i64) -> Result<i64> {
+        let series = match expiry_setting {
+            ExpirySetting::Daily { .. } => EXPIRY_SERIES_DAILY,
+            ExpirySetting::Weekly { .. } => EXPIRY_SERIES_WEEKLY,
+            ExpirySetting::Custom { .. } => EXPIRY_SERIES_CUSTOM,
+        };
+        require!(self.expiry_series & series != 0, CustomError::InvalidExpiry);
+
+        expiry_setting.to_timestamp(stamp_now, self.expiry_hour_utc, self.min_time_to_expiry_secs as i64)
+    }
+
+    //User chosen strikes must sit on the tick grid and within the moneyness band around spot
+    pub fn validate_strike(&self, strike_price: u128, spot_price: u128) -> Result<()> {
+        require!(spot_price > 0, CustomError::InvalidSpotPrice);
+        require!(
+            strike_price > 0 && strike_price.is_multiple_of(self.strike_tick_size as u128),
+            CustomError::InvalidStrikePrice
+        );
+
+        let moneyness_bps = strike_price
+            .checked_mul(BASIS_POINTS_DENOMINATOR as u128).ok_or(CustomError::Overflow)?
+            .checked_div(spot_price).ok_or(CustomError::Overflow)?;
+        require!(
+            (self.min_strike_moneyness_bps as u128..=self.max_strike_moneyness_bps as u128).contains(&moneyness_bps),
+            CustomError::StrikeOutOfRange
+        );
+
+        Ok(())
+    }
 }
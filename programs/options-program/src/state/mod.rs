@@ -1,4 +1,7 @@
  pub mod market;
  pub mod user_account;
+ pub mod protocol_config;
  pub mod event;
- pub mod tests;
\ No newline at end of file
+ pub mod settlement;
+ pub mod option_series;
+ pub mod tests;
@@ -0,0 +1,64 @@
+use anchor_lang::prelude::*;
+use crate::errors::CustomError;
+use crate::state::user_account::OptionOrder;
+
+pub const OPTION_SERIES_SEED: &str = "option_series";
+pub const OPTION_MINT_SEED: &str = "option_mint";
+
+//Tokenized options of one market/type/strike/expiry. The series PDA is the authority of the series mint,
+//one token is one contract of the market contract size
+#[account]
+#[derive(InitSpace)]
+pub struct OptionSeries {
+    pub market_ix: u16,
+    pub option_type: u8,
+    pub strike_price: u64,              // usd scaled by 10^8
+    pub expiry: i64,
+    pub mint: Pubkey,
+    pub supply: u64,                    // Outstanding option tokens
+    pub collateral: u64,                // Committed reserve backing the outstanding tokens, token smallest units
+    pub bump: u8,
+}
+
+impl OptionSeries {
+    pub fn is_initialized(&self) -> bool {
+        self.mint != Pubkey::default()
+    }
+
+    pub fn add(&mut self, quantity: u64, collateral: u64) -> Result<()> {
+        self.supply = self.supply
+            .checked_add(quantity).ok_or(CustomError::Overflow)?;
+        self.collateral = self.collateral
+            .checked_add(collateral).ok_or(CustomError::Overflow)?;
+
+        Ok(())
+    }
+
+    //Outstanding tokens as one position, so exercises pro-rate collateral the same way as partial closes
+    pub fn as_option_order(&self) -> OptionOrder {
+        OptionOrder {
+            strike_price: self.strike_price,
+            expiry: self.expiry,
+            premium: 0,
+            premium_in_usd: 0,
+            quantity: self.supply,
+            max_potential_payout_in_tokens: self.collateral,
+            market_ix: self.market_ix,
+            option_type: self.option_type,
+            ix: 0,
+            is_used: u8::from(self.supply > 0),
+            padding: [0_u8; 3]
+        }
+    }
+
+    //Splits `quantity` tokens worth of option off the series, with their share of the collateral
+    pub fn take(&mut self, quantity: u64) -> Result<OptionOrder> {
+        let mut position = self.as_option_order();
+        let taken = position.take(quantity)?;
+
+        self.supply = position.quantity;
+        self.collateral = position.max_potential_payout_in_tokens;
+
+        Ok(taken)
+    }
+}
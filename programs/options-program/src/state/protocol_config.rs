@@ -0,0 +1,54 @@
+use anchor_lang::prelude::*;
+use crate::{common::Role, errors::CustomError};
+
+pub const PROTOCOL_CONFIG_SEED: &str = "protocol_config";
+pub const MAX_ROLE_GRANTS: usize = 16;
+
+#[account]
+#[derive(InitSpace, PartialEq, Eq)]
+pub struct ProtocolConfig {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,          // Set by propose_admin, Pubkey::default() when no handover is in progress
+    pub bump: u8,
+    #[max_len(MAX_ROLE_GRANTS)]
+    pub role_grants: Vec<RoleGrant>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, InitSpace)]
+pub struct RoleGrant {
+    pub authority: Pubkey,
+    pub roles: u8,                      // Bitfield of Role::mask() values
+}
+
+impl ProtocolConfig {
+    /// Admin implicitly holds every role
+    pub fn has_role(&self, authority: &Pubkey, role: Role) -> bool {
+        if *authority == self.admin {
+            return true;
+        }
+
+        self.role_grants.iter()
+            .any(|g| g.authority == *authority && g.roles & role.mask() != 0)
+    }
+
+    pub fn grant_role(&mut self, authority: Pubkey, role: Role) -> Result<()> {
+        if let Some(grant) = self.role_grants.iter_mut().find(|g| g.authority == authority) {
+            grant.roles |= role.mask();
+            return Ok(());
+        }
+
+        require!(self.role_grants.len() < MAX_ROLE_GRANTS, CustomError::RoleLimitExceeded);
+        self.role_grants.push(RoleGrant { authority, roles: role.mask() });
+
+        Ok(())
+    }
+
+    pub fn revoke_role(&mut self, authority: &Pubkey, role: Role) {
+        if let Some(grant) = self.role_grants.iter_mut().find(|g| g.authority == *authority) {
+            grant.roles &= !role.mask();
+        }
+
+        //Drop entries without roles to free the slot
+        self.role_grants.retain(|g| g.roles != 0);
+    }
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+pub const SETTLEMENT_SEED: &str = "settlement";
+
+//Oracle price snapshot at expiry. European options of the market expiring at `expiry` all settle against it
+#[account]
+#[derive(InitSpace)]
+pub struct Settlement {
+    pub market_ix: u16,
+    pub expiry: i64,
+    pub price: u64,                     // usd scaled by 10^8
+    pub publish_time: i64,              // Oracle publish time of the recorded price
+    pub bump: u8,
+}
@@ -2,33 +2,52 @@ use crate::state::market::*;
 use crate::common::*;
 use anchor_lang::{prelude::Pubkey, solana_program::native_token::LAMPORTS_PER_SOL};
 
+//Shared fixture, modules override only the fields they test
+#[cfg(test)]
+pub(crate) fn mock_market() -> Market {
+    Market {
+        id: 1,
+        fee_bps: 5,
+        lp_minted: 0,
+        premiums: 0,
+        committed_reserve: 0,
+        reserve_supply: 0,
+        name: String::from("1 wSOL market"),
+        bump: 120,
+        vol_surface_bps: [[6000; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES],
+        vol_last_updated: 0,
+        sell_back_spread_bps: 0,
+        contract_size: LAMPORTS_PER_SOL,
+        exercise_style: ExerciseStyle::American,
+        expiry_series: EXPIRY_SERIES_ALL,
+        expiry_hour_utc: 8,
+        min_time_to_expiry_secs: 900,
+        min_strike_moneyness_bps: 8_000,
+        max_strike_moneyness_bps: 12_000,
+        strike_tick_size: 100_000_000,
+        max_price_age_buy_secs: 60,
+        max_price_age_exercise_secs: 60,
+        max_price_conf_bps: 100,
+        exercise_use_ema: false,
+        max_vol_age_secs: 3600,
+        min_volatility_bps: 100,
+        max_volatility_bps: 50_000,
+        max_vol_change_bps: 0,
+        status: 0,
+        keeper_bounty_bps: 0,
+        pricing_model: PricingModel::Legacy,
+        price_feed: String::from("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"),
+        asset_decimals: 9,
+        asset_mint: Pubkey::new_unique()
+    }
+}
+
 #[cfg(test)]
 mod market_issue_lp_shares_tests {
     use crate::math::lp_shares::*;
 
     use super::*;
 
-    fn mock_market() -> Market {
-        Market {
-            id: 1,
-            fee_bps: 2,
-            lp_minted: 0,
-            premiums: 0,
-            committed_reserve: 0,
-            reserve_supply: 0,
-            name: String::from("1 wSOL market"),
-            bump: 120,
-            hour1_volatility_bps: 10000, //1%,
-            hour4_volatility_bps: 10000, //1%,
-            day1_volatility_bps: 10000, //1%,
-            day3_volatility_bps: 10000, //1%,
-            week_volatility_bps: 10000, //1%,
-            vol_last_updated: 0,
-            price_feed: String::from("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"), 
-            asset_decimals: 9,
-            asset_mint: Pubkey::new_unique()
-        }
-    }
 
     #[test]
     fn calc_lp_shares_issues_correct_token_amount() {
@@ -97,6 +116,37 @@ mod market_issue_lp_shares_tests {
         
     }
 
+    #[test]
+    fn capped_withdrawal_keeps_committed_reserve() {
+        //Options lock half of the reserve, the premiums alone don't cover the capped withdrawal's premium part
+        let mut market = mock_market();
+        market.add_liquidity(100, 1).unwrap();
+        market.accrue_premium(10, 50).unwrap();
+
+        let lp_tokens = market.lp_minted;
+        let (withdrawn, burned) = market.remove_liquidity(lp_tokens, 0).unwrap();
+        assert_eq!(withdrawn, 60);
+        assert_eq!((market.reserve_supply, market.premiums, market.committed_reserve), (50, 0, 50));
+
+        //Only the redeemed part is burned, the rest stays with the LP and in lp_minted
+        assert!(burned < lp_tokens);
+        assert_eq!(market.lp_minted, lp_tokens - burned);
+    }
+
+    #[test]
+    fn deposit_into_paid_out_pool_is_rejected() {
+        let mut market = mock_market();
+        market.add_liquidity(100, 1).unwrap();
+        market.accrue_premium(0, 50).unwrap();
+        market.remove_liquidity(market.lp_minted / 2, 0).unwrap();
+        market.pay_out(50).unwrap();
+        market.release_collateral(50).unwrap();
+
+        assert!(market.lp_minted > 0);
+        assert_eq!(market.add_liquidity(100, 1).unwrap_err(), crate::errors::CustomError::InvalidState.into());
+        assert_eq!(market.add_liquidity(u64::MAX, 1).unwrap_err(), crate::errors::CustomError::InvalidState.into());
+    }
+
     // #[test]
     // #[should_panic(expected = "InvalidAmount")]
     // fn calc_lp_shares_panics_when_passed_amount_is_zero() {
@@ -111,17 +161,729 @@ mod market_issue_lp_shares_tests {
 
 #[cfg(test)]
 mod spot_deviations {
-    use crate::common::SpotDeviation;
+    use crate::common::*;
 
     #[test]
     fn test_deviations() {
         let devs = vec![SpotDeviation::N20, SpotDeviation::N15, SpotDeviation::N10,SpotDeviation::N5, SpotDeviation::P5,SpotDeviation::P10,SpotDeviation::P15,SpotDeviation::P20];
 
         for d in devs {
-            println!("D {:?}, Spot {}, Adjusted {}", d, 14_522_926_200u128, d.convert_to_strike(14_522_926_200u128).unwrap());
-            println!("D {:?}, Spot {}, Adjusted {}", d, 40559984u128, d.convert_to_strike(40559984u128).unwrap());
+            println!("D {:?}, Spot {}, Adjusted {}", d, 14_522_926_200u128, d.convert_to_strike(14_522_926_200u128, 100_000_000).unwrap());
+            println!("D {:?}, Spot {}, Adjusted {}", d, 40559984u128, d.convert_to_strike(40559984u128, 1_000_000).unwrap());
+        }
+
+        //$145.23 -10% -> $130.71, rounded to $1 ticks
+        assert_eq!(SpotDeviation::N10.convert_to_strike(14_522_926_200, 100_000_000).unwrap(), 13_100_000_000);
+        //$0.4056 +5% -> $0.4259, rounded to $0.01 ticks
+        assert_eq!(SpotDeviation::P5.convert_to_strike(40_559_984, 1_000_000).unwrap(), 43_000_000);
+        //$97,512.34 -5% -> $92,636.72, rounded to $500 ticks
+        assert_eq!(SpotDeviation::N5.convert_to_strike(9_751_234_000_000, 50_000_000_000).unwrap(), 9_250_000_000_000);
+
+        assert!(SpotDeviation::P0.convert_to_strike(14_522_926_200, 0).is_err());
+    }
+
+    #[test]
+    fn oracle_prices_are_normalized_to_price_decimals() {
+        //Pyth SOL/USD, exponent -8
+        assert_eq!(normalize_price(14_522_926_200, -8).unwrap(), 14_522_926_200);
+        //Exponent -5 is scaled up
+        assert_eq!(normalize_price(14_522_926, -5).unwrap(), 14_522_926_000);
+        //Exponent -10 is scaled down
+        assert_eq!(normalize_price(1_452_292_620_012, -10).unwrap(), 14_522_926_200);
+        //Integer prices
+        assert_eq!(normalize_price(145, 0).unwrap(), 14_500_000_000);
+
+        assert!(normalize_price(u64::MAX, -2).is_err());
+        assert!(normalize_price(1, -40).is_err());
+    }
+    #[test]
+    fn spot_slippage_bounds_move_off_quote() {
+        let guard = SpotSlippage { quoted_spot_price: 15_000_000_000, max_deviation_bps: 50 };
+
+        //0.5% either way is accepted
+        assert!(guard.check(15_075_000_000).is_ok());
+        assert!(guard.check(14_925_000_000).is_ok());
+        assert!(guard.check(15_075_000_001).is_err());
+        assert!(guard.check(14_924_999_999).is_err());
+
+        let zero_quote = SpotSlippage { quoted_spot_price: 0, max_deviation_bps: 50 };
+        assert!(zero_quote.check(15_000_000_000).is_err());
+    }
+}
+
+#[cfg(test)]
+mod black_scholes_reference {
+    use crate::constants::SECONDS_IN_YEAR;
+    use crate::math::{black_scholes, premium::calculate_option_premium};
+
+    use super::*;
+
+    const SCALE: u128 = 100_000_000; // 10^8
+
+    fn years(seconds: u128) -> u128 {
+        seconds * SCALE / SECONDS_IN_YEAR
+    }
+
+    #[test]
+    fn matches_reference_prices() {
+        // (spot, strike, seconds to expiry, vol, call reference, put reference)
+        // Reference prices from closed form Black-Scholes (r = 0) with exact erf, scaled by 10^8
+        let test_cases: Vec<(u128, u128, u128, u128, u64, u64)> = vec![
+            (10_000_000_000, 10_000_000_000, 604_800, 70_000_000, 386_581_126, 386_581_126),
+            (15_000_000_000, 13_500_000_000, 86_400, 60_000_000, 1_500_046_113, 46_113),
+            (15_000_000_000, 16_500_000_000, 86_400, 60_000_000, 166_845, 1_500_166_845),
+            (10_000_000_000, 12_000_000_000, 14_400, 80_000_000, 0, 2_000_000_000),
+            (6_000_000_000_000, 5_700_000_000_000, 259_200, 50_000_000, 317_081_091_715, 17_081_091_715),
+            (40_000_000, 36_000_000, 3_600, 90_000_000, 4_000_000, 0),
+        ];
+
+        for (spot, strike, seconds, vol, call_ref, put_ref) in test_cases {
+            let call = black_scholes::calculate_premium(spot, strike, years(seconds), vol, &OptionType::CALL).unwrap();
+            let put = black_scholes::calculate_premium(spot, strike, years(seconds), vol, &OptionType::PUT).unwrap();
+
+            // A&S normal CDF error is < 7.5e-8, so allow ~2e-7 of spot
+            let tolerance = (spot / 5_000_000) as u64 + 2;
+            println!("Spot {}, Strike {}, Call {} (ref {}), Put {} (ref {})", spot, strike, call, call_ref, put, put_ref);
+            assert!(call.abs_diff(call_ref) <= tolerance, "Call premium off reference");
+            assert!(put.abs_diff(put_ref) <= tolerance, "Put premium off reference");
+        }
+    }
+
+    #[test]
+    fn put_call_parity_holds() {
+        // With r = 0: C - P = S - K
+        let spot: u128 = 14_522_926_200;
+        for strike in [11_000_000_000u128, 14_000_000_000, 15_000_000_000, 17_000_000_000] {
+            let call = black_scholes::calculate_premium(spot, strike, years(259_200), 75_000_000, &OptionType::CALL).unwrap() as i128;
+            let put = black_scholes::calculate_premium(spot, strike, years(259_200), 75_000_000, &OptionType::PUT).unwrap() as i128;
+
+            let parity_error = (call - put) - (spot as i128 - strike as i128);
+            assert!(parity_error.abs() <= 10, "Put-call parity violated by {}", parity_error);
         }
     }
+
+    #[test]
+    fn market_pricing_model_selects_engine() {
+        let mut market = Market {
+            reserve_supply: u64::MAX,
+            vol_surface_bps: [[7000; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES],
+            pricing_model: PricingModel::BlackScholes,
+            ..mock_market()
+        };
+
+        let (bs_usd, _, _) = calculate_option_premium(10_000_000_000, 10_000_000_000, Expiry::WEEK.to_seconds().unwrap(), &market, &OptionType::CALL, 1).unwrap();
+        assert!(bs_usd.abs_diff(386_581_126) <= 2_000);
+
+        market.pricing_model = PricingModel::Legacy;
+        let (legacy_usd, _, _) = calculate_option_premium(10_000_000_000, 10_000_000_000, Expiry::WEEK.to_seconds().unwrap(), &market, &OptionType::CALL, 1).unwrap();
+        assert_ne!(bs_usd, legacy_usd);
+    }
+}
+
+#[cfg(test)]
+mod vol_surface {
+    use crate::math::premium::calculate_option_premium;
+
+    use super::*;
+
+    fn mock_market() -> Market {
+        let mut market = super::mock_market();
+        //Put skew on the day bucket: 80% strike - 90% vol ... 120% strike - 50% vol
+        market.vol_surface_bps[Expiry::DAY1.index()] = [9000, 8500, 8000, 7000, 6000, 5500, 5200, 5100, 5000];
+        market
+    }
+
+    #[test]
+    fn volatility_follows_strike_skew() {
+        let market = mock_market();
+        let spot = 10_000_000_000u128;
+
+        //Nodes
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), SpotDeviation::N20.convert_to_strike(spot, 100_000_000).unwrap(), spot).unwrap(), 9000);
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), spot, spot).unwrap(), 6000);
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), SpotDeviation::P20.convert_to_strike(spot, 100_000_000).unwrap(), spot).unwrap(), 5000);
+
+        //Between nodes, 93% strike -> 60% of the way from 90% (8000) to 95% (7000)
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), 9_300_000_000, spot).unwrap(), 7400);
+
+        //Beyond the surface edges
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), 5_000_000_000, spot).unwrap(), 9000);
+        assert_eq!(market.get_volatility(Expiry::DAY1.to_seconds().unwrap(), 20_000_000_000, spot).unwrap(), 5000);
+
+        //Other buckets are flat
+        assert_eq!(market.get_volatility(Expiry::WEEK.to_seconds().unwrap(), 8_000_000_000, spot).unwrap(), 6000);
+    }
+
+    #[test]
+    fn otm_put_priced_at_skewed_vol() {
+        let mut market = mock_market();
+        market.pricing_model = PricingModel::BlackScholes;
+        let spot = 10_000_000_000u128;
+        let strike = SpotDeviation::N5.convert_to_strike(spot, 100_000_000).unwrap();
+
+        let (skewed, _, _) = calculate_option_premium(strike, spot, Expiry::DAY1.to_seconds().unwrap(), &market, &OptionType::PUT, 1).unwrap();
+        market.vol_surface_bps[Expiry::DAY1.index()] = [6000; VOL_SURFACE_STRIKES];
+        let (flat, _, _) = calculate_option_premium(strike, spot, Expiry::DAY1.to_seconds().unwrap(), &market, &OptionType::PUT, 1).unwrap();
+
+        assert!(skewed > flat, "OTM put should be priced at its skewed vol");
+    }
+
+    #[test]
+    fn surface_bounds_are_validated() {
+        let market = mock_market();
+        let mut surface = [[6000; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES];
+        assert!(market.validate_vol_surface(&surface).is_ok());
+
+        surface[2][3] = 0;
+        assert!(market.validate_vol_surface(&surface).is_err());
+
+        surface[2][3] = 1_000_000;
+        assert!(market.validate_vol_surface(&surface).is_err());
+    }
+
+    #[test]
+    fn surface_change_is_limited() {
+        let mut market = mock_market();
+        let mut surface = market.vol_surface_bps;
+        surface[0][0] += 1_500;
+
+        //No limit configured
+        assert!(market.validate_vol_change(&surface).is_ok());
+
+        market.max_vol_change_bps = 1_000;
+        assert!(market.validate_vol_change(&surface).is_err());
+
+        surface[0][0] = market.vol_surface_bps[0][0] - 1_000;
+        assert!(market.validate_vol_change(&surface).is_ok());
+    }
+
+    #[test]
+    fn surface_goes_stale() {
+        let mut market = mock_market();
+        market.vol_last_updated = 1_000;
+
+        assert!(!market.is_vol_stale(1_000 + market.max_vol_age_secs as i64));
+        assert!(market.is_vol_stale(1_001 + market.max_vol_age_secs as i64));
+    }
+
+    #[test]
+    fn user_strikes_are_validated_against_band_and_tick() {
+        let market = mock_market();
+        let spot = 14_522_926_200u128;
+
+        //7% OTM call, rounded to $1
+        assert!(market.validate_strike(15_500_000_000, spot).is_ok());
+        //Band edges, 80% and 120% of spot
+        assert!(market.validate_strike(11_700_000_000, spot).is_ok());
+        assert!(market.validate_strike(17_400_000_000, spot).is_ok());
+
+        //Off the tick grid
+        assert!(market.validate_strike(15_550_000_000, spot).is_err());
+        assert!(market.validate_strike(0, spot).is_err());
+        //Outside of the band
+        assert!(market.validate_strike(11_600_000_000, spot).is_err());
+        assert!(market.validate_strike(17_500_000_000, spot).is_err());
+        assert!(market.validate_strike(15_500_000_000, 0).is_err());
+    }
+
+    #[test]
+    fn user_strike_between_nodes_is_priced() {
+        let mut market = mock_market();
+        market.pricing_model = PricingModel::BlackScholes;
+        let spot = 10_000_000_000u128;
+
+        let (otm_5, _, _) = calculate_option_premium(10_500_000_000, spot, Expiry::DAY1.to_seconds().unwrap(), &market, &OptionType::CALL, 1).unwrap();
+        let (otm_7, _, _) = calculate_option_premium(10_700_000_000, spot, Expiry::DAY1.to_seconds().unwrap(), &market, &OptionType::CALL, 1).unwrap();
+        let (otm_10, _, _) = calculate_option_premium(11_000_000_000, spot, Expiry::DAY1.to_seconds().unwrap(), &market, &OptionType::CALL, 1).unwrap();
+
+        assert!(otm_5 > otm_7 && otm_7 > otm_10);
+    }
+
+    #[test]
+    fn volatility_is_interpolated_between_tenors() {
+        let market = mock_market();
+        let spot = 10_000_000_000u128;
+        let strike = 8_000_000_000u128;
+        let day = Expiry::DAY1.to_seconds().unwrap();
+
+        //Day bucket at 80% strike is 90%, the 3 day bucket 60%. Halfway in time (2 days) -> 75%
+        assert_eq!(market.get_volatility(day, strike, spot).unwrap(), 9000);
+        assert_eq!(market.get_volatility(2 * day, strike, spot).unwrap(), 7500);
+        assert_eq!(market.get_volatility(3 * day, strike, spot).unwrap(), 6000);
+
+        //Before the first and past the last tenor
+        assert_eq!(market.get_volatility(60, strike, spot).unwrap(), 6000);
+        assert_eq!(market.get_volatility(30 * day, strike, spot).unwrap(), 6000);
+    }
+
+    #[test]
+    fn premium_uses_time_remaining() {
+        let mut market = mock_market();
+        market.pricing_model = PricingModel::BlackScholes;
+        market.vol_surface_bps = [[6000; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES];
+        let spot = 10_000_000_000u128;
+
+        let (full_day, _, _) = calculate_option_premium(spot, spot, 86_400, &market, &OptionType::CALL, 1).unwrap();
+        let (half_day, _, _) = calculate_option_premium(spot, spot, 43_200, &market, &OptionType::CALL, 1).unwrap();
+
+        //ATM premium scales with sqrt(t)
+        assert!(half_day < full_day);
+        let expected = full_day as f64 / 2f64.sqrt();
+        assert!((half_day as f64 - expected).abs() / expected < 0.005);
+    }
+
+    #[test]
+    fn market_lists_expiry_series() {
+        let mut market = mock_market();
+        let now = 1_791_979_200; //2026-10-14 12:00 UTC
+
+        assert_eq!(market.get_expiry_stamp(&ExpirySetting::Daily { offset: 0 }, now).unwrap(), 1_792_051_200);
+
+        market.expiry_series = EXPIRY_SERIES_WEEKLY;
+        assert!(market.get_expiry_stamp(&ExpirySetting::Daily { offset: 0 }, now).is_err());
+        assert!(market.get_expiry_stamp(&ExpirySetting::Custom { expiry_stamp: now + 3600 }, now).is_err());
+        assert_eq!(market.get_expiry_stamp(&ExpirySetting::Weekly { offset: 0 }, now).unwrap(), 1_792_137_600);
+    }
+}
+
+#[cfg(test)]
+mod expiry_series {
+    use super::*;
+
+    const WED_12_00: i64 = 1_791_979_200;   //2026-10-14 12:00 UTC, Wednesday
+    const THU_08_00: i64 = 1_792_051_200;
+    const FRI_08_00: i64 = 1_792_137_600;
+    const DAY: i64 = 86_400;
+
+    #[test]
+    fn daily_series_rolls_at_expiry_hour() {
+        let daily = ExpirySetting::Daily { offset: 0 };
+        assert_eq!(daily.to_timestamp(WED_12_00, 8, 900).unwrap(), THU_08_00);
+        assert_eq!(ExpirySetting::Daily { offset: 2 }.to_timestamp(WED_12_00, 8, 900).unwrap(), THU_08_00 + 2 * DAY);
+
+        //Options bought at different times share the expiry
+        assert_eq!(daily.to_timestamp(WED_12_00 + 3 * 3600, 8, 900).unwrap(), THU_08_00);
+
+        //Closer than the min time to expiry, rolls to the next day
+        assert_eq!(daily.to_timestamp(THU_08_00 - 600, 8, 900).unwrap(), THU_08_00 + DAY);
+        assert_eq!(daily.to_timestamp(THU_08_00 - 900, 8, 900).unwrap(), THU_08_00);
+    }
+
+    #[test]
+    fn weekly_series_expires_on_friday() {
+        let weekly = ExpirySetting::Weekly { offset: 0 };
+        assert_eq!(weekly.to_timestamp(WED_12_00, 8, 900).unwrap(), FRI_08_00);
+        assert_eq!(weekly.to_timestamp(FRI_08_00 - 3600, 8, 900).unwrap(), FRI_08_00);
+        assert_eq!(weekly.to_timestamp(FRI_08_00 - 600, 8, 900).unwrap(), FRI_08_00 + 7 * DAY);
+        assert_eq!(weekly.to_timestamp(FRI_08_00 + DAY, 8, 900).unwrap(), FRI_08_00 + 7 * DAY);
+        assert_eq!(ExpirySetting::Weekly { offset: 3 }.to_timestamp(WED_12_00, 8, 900).unwrap(), FRI_08_00 + 21 * DAY);
+
+        //Beyond 30 days
+        assert!(ExpirySetting::Weekly { offset: 5 }.to_timestamp(WED_12_00, 8, 900).is_err());
+    }
+
+    #[test]
+    fn custom_expiry_is_bounded() {
+        let custom = |expiry_stamp| ExpirySetting::Custom { expiry_stamp };
+        assert_eq!(custom(WED_12_00 + 7_777).to_timestamp(WED_12_00, 8, 900).unwrap(), WED_12_00 + 7_777);
+        assert_eq!(custom(WED_12_00 + 30 * DAY).to_timestamp(WED_12_00, 8, 900).unwrap(), WED_12_00 + 30 * DAY);
+
+        assert!(custom(WED_12_00 + 30 * DAY + 1).to_timestamp(WED_12_00, 8, 900).is_err());
+        assert!(custom(WED_12_00 + 899).to_timestamp(WED_12_00, 8, 900).is_err());
+        assert!(custom(WED_12_00 - 1).to_timestamp(WED_12_00, 8, 900).is_err());
+    }
+
+    #[test]
+    fn time_distance() {
+        assert_eq!(calc_time_distance(WED_12_00, THU_08_00).unwrap(), 20 * 3600);
+        assert!(calc_time_distance(WED_12_00, WED_12_00).is_err());
+        assert!(calc_time_distance(WED_12_00, WED_12_00 + 31 * DAY).is_err());
+    }
+}
+
+#[cfg(test)]
+mod oracle_price {
+    use anchor_lang::prelude::Clock;
+    use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+    use crate::state::settlement::Settlement;
+
+    use super::*;
+
+    const SOL_USD_FEED: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+    fn mock_market() -> Market {
+        Market {
+            max_price_age_exercise_secs: 30,
+            max_price_conf_bps: 50, //0.5%
+            price_feed: String::from(SOL_USD_FEED),
+            ..super::mock_market()
+        }
+    }
+
+    fn mock_price_update(price: i64, conf: u64, ema_price: i64, publish_time: i64) -> PriceUpdateV2 {
+        PriceUpdateV2 {
+            write_authority: Pubkey::new_unique(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: get_feed_id_from_hex(SOL_USD_FEED).unwrap(),
+                price,
+                conf,
+                exponent: -8,
+                publish_time,
+                prev_publish_time: publish_time - 1,
+                ema_price,
+                ema_conf: conf,
+            },
+            posted_slot: 0
+        }
+    }
+
+    fn clock_at(unix_timestamp: i64) -> Clock {
+        Clock { unix_timestamp, ..Clock::default() }
+    }
+
+    #[test]
+    fn rejects_stale_and_uncertain_prices() {
+        let market = mock_market();
+        let update = mock_price_update(15_000_000_000, 15_000_000, 14_900_000_000, 1_000);
+
+        assert!(market.get_oracle_price(&update, &clock_at(1_060), market.max_price_age_buy_secs, false).is_ok());
+        assert!(market.get_oracle_price(&update, &clock_at(1_061), market.max_price_age_buy_secs, false).is_err());
+        assert!(market.get_oracle_price(&update, &clock_at(1_031), market.max_price_age_exercise_secs, false).is_err());
+
+        //1% confidence interval over the 0.5% limit
+        let update = mock_price_update(15_000_000_000, 150_000_000, 14_900_000_000, 1_000);
+        assert!(market.get_oracle_price(&update, &clock_at(1_000), market.max_price_age_buy_secs, false).is_err());
+    }
+
+    #[test]
+    fn uses_ema_price_when_requested() {
+        let market = mock_market();
+        let update = mock_price_update(15_000_000_000, 15_000_000, 14_900_000_000, 1_000);
+
+        let spot = market.get_oracle_price(&update, &clock_at(1_000), market.max_price_age_exercise_secs, false).unwrap();
+        let ema = market.get_oracle_price(&update, &clock_at(1_000), market.max_price_age_exercise_secs, true).unwrap();
+        assert_eq!(spot.price, 15_000_000_000);
+        assert_eq!(ema.price, 14_900_000_000);
+    }
+
+    #[test]
+    fn buyer_pays_worse_side_of_confidence_interval() {
+        assert_eq!(buyer_spot_price(15_000_000_000, 15_000_000, &OptionType::CALL).unwrap(), 15_015_000_000);
+        assert_eq!(buyer_spot_price(15_000_000_000, 15_000_000, &OptionType::PUT).unwrap(), 14_985_000_000);
+    }
+
+    #[test]
+    fn european_options_exercise_at_recorded_settlement() {
+        let mut market = mock_market();
+        market.exercise_style = ExerciseStyle::European;
+        let expiry = 1_000;
+        let update = mock_price_update(16_000_000_000, 15_000_000, 16_000_000_000, 1_010);
+        let settlement = Settlement { market_ix: market.id, expiry, price: 15_000_000_000, publish_time: 1_001, bump: 255 };
+
+        //Settlement price wins over the current oracle price
+        assert_eq!(market.get_exercise_price(expiry, &update, Some(&settlement), &clock_at(1_010)).unwrap(), 15_000_000_000);
+
+        //Not before expiry, and not without the settlement of the same market and expiry
+        assert!(market.get_exercise_price(expiry, &update, Some(&settlement), &clock_at(999)).is_err());
+        assert!(market.get_exercise_price(expiry, &update, None, &clock_at(1_010)).is_err());
+        let other_expiry = Settlement { expiry: 2_000, ..settlement.clone() };
+        assert!(market.get_exercise_price(expiry, &update, Some(&other_expiry), &clock_at(1_010)).is_err());
+        let other_market = Settlement { market_ix: 2, ..settlement.clone() };
+        assert!(market.get_exercise_price(expiry, &update, Some(&other_market), &clock_at(1_010)).is_err());
+    }
+
+    #[test]
+    fn american_options_exercise_at_oracle_price() {
+        let market = mock_market();
+        let update = mock_price_update(16_000_000_000, 15_000_000, 15_500_000_000, 990);
+
+        //Before expiry, settlement ignored
+        assert_eq!(market.get_exercise_price(1_000, &update, None, &clock_at(995)).unwrap(), 16_000_000_000);
+    }
+}
+
+#[cfg(test)]
+mod exercise_payout {
+    use crate::constants::{EUROPEAN_EXERCISE_WINDOW_SECS, EXERCISE_INTERVAL_TOLERANCE};
+    use crate::math::payout::*;
+    use crate::state::option_series::OptionSeries;
+    use crate::state::user_account::OptionOrder;
+
+    use super::*;
+
+    fn mock_option(option_type: OptionType, strike_price: u64, quantity: u64, max_payout: u64) -> OptionOrder {
+        OptionOrder {
+            strike_price,
+            expiry: 0,
+            premium: 0,
+            premium_in_usd: 0,
+            quantity,
+            max_potential_payout_in_tokens: max_payout,
+            market_ix: 1,
+            option_type: u8::from(option_type),
+            ix: 0,
+            is_used: 1,
+            padding: [0_u8; 3]
+        }
+    }
+
+    #[test]
+    fn payout_is_profit_in_tokens_capped_by_collateral() {
+        let market = mock_market();
+
+        //Call struck at $100, spot at $125, 2 units -> $50 profit = 0.4 SOL
+        let option = mock_option(OptionType::CALL, 10_000_000_000, 2, LAMPORTS_PER_SOL);
+        let (profit_usd, payout) = calculate_exercise_payout(&option, 12_500_000_000, &market).unwrap();
+        assert_eq!(profit_usd, 5_000_000_000);
+        assert_eq!(payout, 400_000_000);
+
+        //Same option with less collateral locked gets capped
+        let option = mock_option(OptionType::CALL, 10_000_000_000, 2, 100_000_000);
+        let (_, payout) = calculate_exercise_payout(&option, 12_500_000_000, &market).unwrap();
+        assert_eq!(payout, 100_000_000);
+
+        //Out of the money put pays nothing
+        let option = mock_option(OptionType::PUT, 10_000_000_000, 2, LAMPORTS_PER_SOL);
+        let (profit_usd, payout) = calculate_exercise_payout(&option, 12_500_000_000, &market).unwrap();
+        assert_eq!((profit_usd, payout), (0, 0));
+    }
+
+    #[test]
+    fn option_lapses_after_exercise_window() {
+        let mut option = mock_option(OptionType::CALL, 10_000_000_000, 1, LAMPORTS_PER_SOL);
+        option.expiry = 1_000;
+
+        let american = mock_market().exercise_window();
+        assert_eq!(american, EXERCISE_INTERVAL_TOLERANCE);
+        assert!(!option.is_lapsed(1_000, american));
+        assert!(!option.is_lapsed(1_000 + EXERCISE_INTERVAL_TOLERANCE, american));
+        assert!(option.is_lapsed(1_001 + EXERCISE_INTERVAL_TOLERANCE, american));
+
+        //European options settle at the recorded price, so they stay exercisable for longer
+        let european = Market { exercise_style: ExerciseStyle::European, ..mock_market() }.exercise_window();
+        assert_eq!(european, EUROPEAN_EXERCISE_WINDOW_SECS);
+        assert!(!option.is_lapsed(1_001 + EXERCISE_INTERVAL_TOLERANCE, european));
+        assert!(option.is_lapsed(1_001 + EUROPEAN_EXERCISE_WINDOW_SECS, european));
+
+        option.clear();
+        assert!(!option.is_lapsed(1_001 + EXERCISE_INTERVAL_TOLERANCE, american), "Unused slot can't lapse");
+    }
+
+    #[test]
+    fn partial_close_pro_rates_collateral_and_premium() {
+        let mut option = mock_option(OptionType::CALL, 10_000_000_000, 3, 1_000_000_000);
+        option.premium = 30_000_000;
+        option.premium_in_usd = 300_000_000;
+
+        let taken = option.take(1).unwrap();
+        assert_eq!((taken.quantity, taken.max_potential_payout_in_tokens, taken.premium, taken.premium_in_usd), (1, 333_333_333, 10_000_000, 100_000_000));
+        assert_eq!((option.quantity, option.max_potential_payout_in_tokens, option.premium, option.premium_in_usd), (2, 666_666_667, 20_000_000, 200_000_000));
+        assert!(option.is_initialized());
+
+        //Payout of the exercised part is capped by its share of collateral
+        let (_, payout) = calculate_exercise_payout(&taken, 100_000_000_000, &mock_market()).unwrap();
+        assert_eq!(payout, 333_333_333);
+
+        //Closing the rest releases everything left and frees the slot
+        assert!(option.take(3).is_err());
+        assert!(option.take(0).is_err());
+        let rest = option.take(2).unwrap();
+        assert_eq!(rest.max_potential_payout_in_tokens + taken.max_potential_payout_in_tokens, 1_000_000_000);
+        assert!(!option.is_initialized());
+    }
+
+    #[test]
+    fn option_series_tracks_supply_and_collateral() {
+        let mut series = OptionSeries {
+            market_ix: 1,
+            option_type: u8::from(OptionType::PUT),
+            strike_price: 10_000_000_000,
+            expiry: 1_000,
+            mint: Pubkey::new_unique(),
+            supply: 0,
+            collateral: 0,
+            bump: 255
+        };
+        assert!(!series.as_option_order().is_initialized());
+
+        //Two buys into the same series
+        series.add(10, 400_000_000).unwrap();
+        series.add(5, 200_000_000).unwrap();
+        assert_eq!((series.supply, series.collateral), (15, 600_000_000));
+
+        //Exercising a third of the tokens takes a third of the collateral
+        let exercised = series.take(5).unwrap();
+        assert_eq!((exercised.quantity, exercised.max_potential_payout_in_tokens, exercised.strike_price), (5, 200_000_000, 10_000_000_000));
+        assert_eq!((series.supply, series.collateral), (10, 400_000_000));
+
+        //Put struck at $100, spot at $80 -> $20 * 5 = 1.25 SOL, capped at 0.2 SOL
+        let (profit_usd, payout) = calculate_exercise_payout(&exercised, 8_000_000_000, &mock_market()).unwrap();
+        assert_eq!((profit_usd, payout), (10_000_000_000, 200_000_000));
+
+        assert!(series.take(11).is_err());
+        assert!(series.as_option_order().is_lapsed(1_001 + EXERCISE_INTERVAL_TOLERANCE, EXERCISE_INTERVAL_TOLERANCE));
+    }
+
+    fn mock_market() -> Market {
+        Market {
+            sell_back_spread_bps: 200, //2%
+            pricing_model: PricingModel::BlackScholes,
+            ..super::mock_market()
+        }
+    }
+
+    #[test]
+    fn sell_back_pays_model_value_minus_spread() {
+        let market = mock_market();
+        let spot = 10_000_000_000u128;
+        let option = mock_option(OptionType::CALL, 10_000_000_000, 2, LAMPORTS_PER_SOL);
+
+        let (_, value_tokens, _) = crate::math::premium::calculate_option_premium(spot, spot, 43_200, &market, &OptionType::CALL, 2).unwrap();
+        let (_, payout, spread) = calculate_sell_back_payout(&option, spot, 43_200, &market).unwrap();
+
+        assert_eq!(spread, value_tokens * 200 / 10_000);
+        assert_eq!(payout + spread, value_tokens);
+
+        //Less time left, less paid back
+        let (_, later_payout, _) = calculate_sell_back_payout(&option, spot, 3_600, &market).unwrap();
+        assert!(later_payout < payout);
+    }
+
+    #[test]
+    fn sell_back_is_capped_by_collateral() {
+        let market = mock_market();
+        //Deep in the money call, $50 intrinsic on 0.1 SOL collateral
+        let option = mock_option(OptionType::CALL, 10_000_000_000, 1, 100_000_000);
+
+        let (_, payout, spread) = calculate_sell_back_payout(&option, 15_000_000_000, 43_200, &market).unwrap();
+        assert_eq!(payout, 100_000_000);
+        assert!(spread > 0);
+    }
+
+    #[test]
+    fn worthless_option_sells_back_for_nothing() {
+        let market = mock_market();
+        //$180 call with spot at $100 and an hour left
+        let option = mock_option(OptionType::CALL, 18_000_000_000, 1, LAMPORTS_PER_SOL);
+
+        assert_eq!(calculate_sell_back_payout(&option, 10_000_000_000, 3_600, &market).unwrap(), (0, 0, 0));
+    }
+
+    #[test]
+    fn seller_gets_worse_side_of_confidence_interval() {
+        assert_eq!(seller_spot_price(15_000_000_000, 15_000_000, &OptionType::CALL).unwrap(), 14_985_000_000);
+        assert_eq!(seller_spot_price(15_000_000_000, 15_000_000, &OptionType::PUT).unwrap(), 15_015_000_000);
+    }
+
+    #[test]
+    fn contract_size_scales_premium_collateral_and_payout() {
+        use crate::math::premium::{calculate_collateral, calculate_option_premium};
+
+        let whole = mock_market();
+        let mut tenth = mock_market();
+        tenth.contract_size = LAMPORTS_PER_SOL / 10;
+        let spot = 10_000_000_000u128;
+
+        //10 contracts of 0.1 SOL cost as much as 1 whole SOL
+        let (whole_usd, whole_tokens, _) = calculate_option_premium(spot, spot, 86_400, &whole, &OptionType::CALL, 1).unwrap();
+        let (tenth_usd, tenth_tokens, _) = calculate_option_premium(spot, spot, 86_400, &tenth, &OptionType::CALL, 10).unwrap();
+        assert_eq!((tenth_usd, tenth_tokens), (whole_usd, whole_tokens));
+
+        //3 contracts of 0.1 SOL
+        let (one_usd, _, _) = calculate_option_premium(spot, spot, 86_400, &tenth, &OptionType::CALL, 1).unwrap();
+        assert_eq!(one_usd, whole_usd / 10);
+        let (whole_collateral, _) = calculate_collateral(spot, spot, &OptionType::CALL, &whole, 86_400, 1).unwrap();
+        let (tenth_collateral, _) = calculate_collateral(spot, spot, &OptionType::CALL, &tenth, 86_400, 3).unwrap();
+        assert_eq!(tenth_collateral, whole_collateral * 3 / 10);
+
+        //Call struck at $100, spot at $125, 0.3 SOL -> $7.5 profit = 0.06 SOL
+        let option = mock_option(OptionType::CALL, 10_000_000_000, 3, LAMPORTS_PER_SOL);
+        let (profit_usd, payout) = calculate_exercise_payout(&option, 12_500_000_000, &tenth).unwrap();
+        assert_eq!((profit_usd, payout), (750_000_000, 60_000_000));
+    }
+}
+
+#[cfg(test)]
+mod user_account_pages {
+    use crate::state::user_account::*;
+
+    use super::*;
+
+    fn page_address(owner: &Pubkey, page: u8) -> Pubkey {
+        Pubkey::find_program_address(&[USR_ACC_SEED.as_bytes(), owner.as_ref(), UserAccount::page_seed(page).as_ref()], &crate::ID).0
+    }
+
+    #[test]
+    fn first_page_keeps_original_address() {
+        let owner = Pubkey::new_unique();
+        let original = Pubkey::find_program_address(&[USR_ACC_SEED.as_bytes(), owner.as_ref()], &crate::ID).0;
+
+        assert_eq!(page_address(&owner, 0), original);
+    }
+
+    #[test]
+    fn pages_have_distinct_addresses() {
+        let owner = Pubkey::new_unique();
+        let pages: Vec<Pubkey> = (0..=u8::MAX).map(|page| page_address(&owner, page)).collect();
+
+        for (i, page) in pages.iter().enumerate() {
+            assert!(!pages[i + 1..].contains(page));
+        }
+        assert_ne!(page_address(&owner, 1), page_address(&Pubkey::new_unique(), 1));
+    }
+}
+
+#[cfg(test)]
+mod role_registry {
+    use crate::state::protocol_config::*;
+
+    use super::*;
+
+    fn mock_config() -> ProtocolConfig {
+        ProtocolConfig {
+            admin: Pubkey::new_unique(),
+            pending_admin: Pubkey::default(),
+            bump: 255,
+            role_grants: vec![]
+        }
+    }
+
+    #[test]
+    fn grant_and_revoke_roles() {
+        let mut config = mock_config();
+        let vol_bot = Pubkey::new_unique();
+
+        //Admin holds every role implicitly
+        let admin = config.admin;
+        assert!(config.has_role(&admin, Role::FeeCollector));
+        assert!(!config.has_role(&vol_bot, Role::VolKeeper));
+
+        config.grant_role(vol_bot, Role::VolKeeper).unwrap();
+        config.grant_role(vol_bot, Role::Pauser).unwrap();
+        assert_eq!(config.role_grants.len(), 1);
+        assert!(config.has_role(&vol_bot, Role::VolKeeper));
+        assert!(config.has_role(&vol_bot, Role::Pauser));
+        assert!(!config.has_role(&vol_bot, Role::FeeCollector), "Vol keeper must not sweep fees");
+
+        config.revoke_role(&vol_bot, Role::VolKeeper);
+        assert!(!config.has_role(&vol_bot, Role::VolKeeper));
+        assert!(config.has_role(&vol_bot, Role::Pauser));
+
+        config.revoke_role(&vol_bot, Role::Pauser);
+        assert!(config.role_grants.is_empty(), "Grant without roles should be removed");
+    }
+
+    #[test]
+    fn grant_role_fails_when_registry_full() {
+        let mut config = mock_config();
+
+        for _ in 0..MAX_ROLE_GRANTS {
+            config.grant_role(Pubkey::new_unique(), Role::VolKeeper).unwrap();
+        }
+
+        assert!(config.grant_role(Pubkey::new_unique(), Role::VolKeeper).is_err());
+    }
 }
 
 #[cfg(test)]
@@ -132,23 +894,15 @@ mod premium_display {
 
     fn mock_market() -> Market {
         Market {
-            id: 1,
-            fee_bps: 5,
-            lp_minted: 0,
-            premiums: 0,
-            committed_reserve: 0,
             reserve_supply: u64::MAX,
-            name: String::from("1 wSOL market"),
-            bump: 120,
-            hour1_volatility_bps: 8000, //80%,
-            hour4_volatility_bps: 7000, //70%,
-            day1_volatility_bps: 6000, 
-            day3_volatility_bps: 7000, 
-            week_volatility_bps: 7000, 
-            vol_last_updated: 0,
-            price_feed: String::from("0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d"), 
-            asset_decimals: 9,
-            asset_mint: Pubkey::new_unique()
+            vol_surface_bps: [
+                [8000; VOL_SURFACE_STRIKES], //80%
+                [7000; VOL_SURFACE_STRIKES], //70%
+                [6000; VOL_SURFACE_STRIKES],
+                [7000; VOL_SURFACE_STRIKES],
+                [7000; VOL_SURFACE_STRIKES],
+            ],
+            ..super::mock_market()
         }
     }
 
@@ -175,7 +929,7 @@ mod premium_display {
            let (prem_h1,fee1,_) = calculate_option_premium(
                 strike, 
                 spot,
-                Expiry::HOUR1,
+                Expiry::HOUR1.to_seconds().unwrap(),
                 market,
                 &OptionType::CALL,
                 1
@@ -184,7 +938,7 @@ mod premium_display {
             let (prem_h4,fee2,_) = calculate_option_premium(
                 strike, 
                 spot,
-                Expiry::HOUR4,
+                Expiry::HOUR4.to_seconds().unwrap(),
                 market,
                 &OptionType::CALL,
                 1
@@ -193,7 +947,7 @@ mod premium_display {
             let (prem_d,fee3,_) = calculate_option_premium(
                 strike, 
                 spot,
-                Expiry::DAY1,
+                Expiry::DAY1.to_seconds().unwrap(),
                 market,
                 &OptionType::CALL,
                 1
@@ -202,7 +956,7 @@ mod premium_display {
             let (prem_3d,fee4,_) = calculate_option_premium(
                 strike, 
                 spot,
-                Expiry::DAY3,
+                Expiry::DAY3.to_seconds().unwrap(),
                 market,
                 &OptionType::CALL,
                 1
@@ -211,17 +965,17 @@ mod premium_display {
             let (prem_7d, fee5,_) = calculate_option_premium(
                 strike, 
                 spot,
-                Expiry::DAY3,
+                Expiry::DAY3.to_seconds().unwrap(),
                 market,
                 &OptionType::CALL,
                 1
             ).unwrap();
 
-            let (x1, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::HOUR1, 1).unwrap();
-            let (x2, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::HOUR4, 1).unwrap();
-            let (x3, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::DAY1, 1).unwrap();
-            let (x4, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::DAY3, 1).unwrap();
-            let (x5, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::WEEK, 1).unwrap();
+            let (x1, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::HOUR1.to_seconds().unwrap(), 1).unwrap();
+            let (x2, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::HOUR4.to_seconds().unwrap(), 1).unwrap();
+            let (x3, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::DAY1.to_seconds().unwrap(), 1).unwrap();
+            let (x4, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::DAY3.to_seconds().unwrap(), 1).unwrap();
+            let (x5, _) = calculate_collateral(strike, spot, &OptionType::CALL, market, Expiry::WEEK.to_seconds().unwrap(), 1).unwrap();
 
            
             // Print for debugging
@@ -336,4 +1090,127 @@ mod premium_display {
 //             // println!("Number: {}, ln {}, lnint_poly {} ({})", nf64, nf64.ln(), lnint_poly, fixed_to_float(lnint_poly));
 //         }
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod lp_share_invariants {
+    use proptest::prelude::*;
+
+    use super::*;
+
+    const LPS: usize = 3;
+
+    #[derive(Debug, Clone)]
+    enum Op {
+        Deposit { lp: usize, amount: u64 },
+        Withdraw { lp: usize, bps: u64 },
+        Buy { premium: u64, collateral: u64 },
+        Exercise { option: usize, payout_bps: u64 },
+    }
+
+    //Pool balances and the holders of its LP tokens and options
+    struct Pool {
+        market: Market,
+        lp_balances: [u64; LPS],
+        options: Vec<u64>,
+    }
+
+    fn tvl(market: &Market) -> u128 {
+        market.reserve_supply as u128 + market.premiums as u128
+    }
+
+    //(reserve, committed, premiums, lp minted) for failure messages
+    fn balances(market: &Market) -> (u64, u64, u64, u64) {
+        (market.reserve_supply, market.committed_reserve, market.premiums, market.lp_minted)
+    }
+
+    //tvl / lp_minted didn't go down, compared without dividing
+    fn share_price_held(before: &Market, after: &Market) -> bool {
+        before.lp_minted == 0 || after.lp_minted == 0
+            || tvl(after) * before.lp_minted as u128 >= tvl(before) * after.lp_minted as u128
+    }
+
+    fn amount() -> impl Strategy<Value = u64> {
+        prop_oneof![1..1_000_u64, 1..1_000_000 * LAMPORTS_PER_SOL, any::<u64>()]
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (0..LPS, amount()).prop_map(|(lp, amount)| Op::Deposit { lp, amount }),
+            (0..LPS, 1..=10_000_u64).prop_map(|(lp, bps)| Op::Withdraw { lp, bps }),
+            (amount(), amount()).prop_map(|(premium, collateral)| Op::Buy { premium, collateral }),
+            (any::<usize>(), 0..=10_000_u64).prop_map(|(option, payout_bps)| Op::Exercise { option, payout_bps }),
+        ]
+    }
+
+    //Applies an op the way its instruction does. A failed instruction reverts, so errors leave the pool as it was
+    fn apply(pool: &mut Pool, op: &Op) -> std::result::Result<(), TestCaseError> {
+        let before = pool.market.clone();
+        let mut market = pool.market.clone();
+
+        match *op {
+            Op::Deposit { lp, amount } => {
+                //Depositing and redeeming right away never returns more than was put in
+                let mut round_trip = market.clone();
+                if let Ok(lp_tokens) = round_trip.add_liquidity(amount, 1) {
+                    if let Ok((withdrawn, _)) = round_trip.remove_liquidity(lp_tokens, 0) {
+                        prop_assert!(withdrawn <= amount, "round trip of {} returned {}", amount, withdrawn);
+                    }
+                }
+
+                if let Ok(lp_tokens) = market.add_liquidity(amount, 1) {
+                    prop_assert!(lp_tokens > 0);
+                    prop_assert!(share_price_held(&before, &market), "deposit diluted LPs: {:?} -> {:?}", balances(&before), balances(&market));
+                    pool.lp_balances[lp] += lp_tokens;
+                    pool.market = market;
+                }
+            }
+            Op::Withdraw { lp, bps } => {
+                let requested = (pool.lp_balances[lp] as u128 * bps as u128 / 10_000) as u64;
+                if let Ok((withdrawn, burned)) = market.remove_liquidity(requested, 0) {
+                    prop_assert!(burned <= requested);
+                    prop_assert!(withdrawn as u128 <= tvl(&before) - before.committed_reserve as u128, "committed reserve withdrawn");
+                    prop_assert!(share_price_held(&before, &market), "withdrawal diluted LPs: {:?} -> {:?}", balances(&before), balances(&market));
+                    pool.lp_balances[lp] -= burned;
+                    pool.market = market;
+                }
+            }
+            Op::Buy { premium, collateral } => {
+                //Same collateral check as the buy instructions
+                if market.reserve_supply - market.committed_reserve > collateral && market.accrue_premium(premium, collateral).is_ok() {
+                    pool.options.push(collateral);
+                    pool.market = market;
+                }
+            }
+            Op::Exercise { option, payout_bps } => {
+                if !pool.options.is_empty() {
+                    let collateral = pool.options[option % pool.options.len()];
+                    let payout = (collateral as u128 * payout_bps as u128 / 10_000) as u64;
+                    if market.pay_out(payout).and_then(|_| market.release_collateral(collateral)).is_ok() {
+                        pool.options.swap_remove(option % pool.options.len());
+                        pool.market = market;
+                    }
+                }
+            }
+        }
+
+        let market = &pool.market;
+        prop_assert!(market.reserve_supply >= market.committed_reserve, "committed reserve not backed: {:?}", balances(market));
+        prop_assert_eq!(market.committed_reserve, pool.options.iter().sum::<u64>());
+        prop_assert_eq!(market.lp_minted, pool.lp_balances.iter().sum::<u64>());
+
+        Ok(())
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2_000))]
+
+        #[test]
+        fn lp_share_math_holds_over_random_operations(ops in prop::collection::vec(op(), 1..64)) {
+            let mut pool = Pool { market: mock_market(), lp_balances: [0; LPS], options: Vec::new() };
+            for op in &ops {
+                apply(&mut pool, op)?;
+            }
+        }
+    }
+}
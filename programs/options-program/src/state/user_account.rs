@@ -1,5 +1,6 @@
 use anchor_lang::prelude::*;
 use crate::common::OptionType;
+use crate::errors::CustomError;
 
 pub const USR_ACC_SEED: &str = "account";
 
@@ -32,6 +33,41 @@ impl OptionOrder {
         self.is_used == 1        
     }
 
+    //Exercise window of the option's market has closed, the option can only be settled as expired
+    pub fn is_lapsed(&self, stamp_now: i64, exercise_window: i64) -> bool {
+        self.is_initialized() && stamp_now > self.expiry + exercise_window
+    }
+
+    //Splits `quantity` units off the option, with pro-rated collateral and premium. The rest stays in the slot,
+    //closing the whole quantity clears it
+    pub fn take(&mut self, quantity: u64) -> Result<OptionOrder> {
+        require!(quantity > 0 && quantity <= self.quantity, CustomError::InvalidQuantity);
+
+        if quantity == self.quantity {
+            let taken = *self;
+            self.clear();
+            return Ok(taken);
+        }
+
+        let pro_rate = |value: u64| -> Result<u64> {
+            u64::try_from((value as u128) * (quantity as u128) / (self.quantity as u128))
+                .map_err(|_| CustomError::Overflow.into())
+        };
+
+        let mut taken = *self;
+        taken.quantity = quantity;
+        taken.max_potential_payout_in_tokens = pro_rate(self.max_potential_payout_in_tokens)?;
+        taken.premium = pro_rate(self.premium)?;
+        taken.premium_in_usd = pro_rate(self.premium_in_usd)?;
+
+        self.quantity -= quantity;
+        self.max_potential_payout_in_tokens -= taken.max_potential_payout_in_tokens;
+        self.premium -= taken.premium;
+        self.premium_in_usd -= taken.premium_in_usd;
+
+        Ok(taken)
+    }
+
     pub fn clear(&mut self) {
         self.expiry = 0;
         self.market_ix = 0;
@@ -46,6 +82,11 @@ impl OptionOrder {
 }
 
 impl UserAccount {
+    //Extra seed of a position page. Page 0 has none, so it keeps the address of the original single account
+    pub fn page_seed(page: u8) -> Vec<u8> {
+        if page == 0 { vec![] } else { vec![page] }
+    }
+
     pub fn get_available_slot(&self) -> Option<usize> {
         self.options.iter()
         .position(|o| !o.is_initialized())
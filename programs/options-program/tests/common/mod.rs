@@ -0,0 +1,398 @@
+#![allow(dead_code)]
+
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use anchor_lang::prelude::{AccountInfo, Pubkey};
+use anchor_lang::solana_program::{entrypoint::ProgramResult, program_pack::Pack};
+use anchor_lang::{AccountDeserialize, AccountSerialize, Space};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use options_program::common::*;
+use options_program::errors::CustomError;
+use options_program::state::{market::*, protocol_config::*, user_account::UserAccount};
+use options_program_sdk::{accounts::decode_user_account, instructions::*, pda::*};
+use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceFeedMessage, PriceUpdateV2, VerificationLevel};
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::{
+    account::Account,
+    bpf_loader,
+    clock::Clock,
+    compute_budget::ComputeBudgetInstruction,
+    instruction::{Instruction, InstructionError},
+    native_token::LAMPORTS_PER_SOL,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_program,
+    transaction::{Transaction, TransactionError},
+};
+
+static NONCE: AtomicU32 = AtomicU32::new(0);
+
+pub const SOL_USD_FEED: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+pub const MARKET_IX: u16 = 1;
+pub const ASSET_DECIMALS: u8 = 9;
+pub const START: i64 = 1_767_744_000; //Wednesday 2026-01-07 00:00 UTC, first daily expiry is at 08:00
+pub const SPOT: i64 = 15_000_000_000; //$150, scaled by 10^8
+
+//Output of `anchor build`
+const PROGRAM_SO: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../target/deploy/options_program.so");
+
+//Native fallback when the program wasn't built for SBF. Anchor's entrypoint ties the account slice and account info
+//lifetimes together, which the native processor signature doesn't. The accounts only live for the instruction,
+//so leaking them is fine in tests
+fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let accounts = Box::leak(Box::new(accounts.to_vec()));
+    options_program::entry(program_id, accounts, data)
+}
+
+pub struct TestEnv {
+    pub ctx: ProgramTestContext,
+    pub admin: Keypair,
+    pub asset_mint: Pubkey,
+    pub price_update: Pubkey,
+    pub now: i64,
+}
+
+impl TestEnv {
+    //Program loaded from the SBF build, with the protocol config and an asset mint in genesis. The SBF program runs
+    //in the VM with its compute, stack and heap limits. Without a build (no Solana toolchain) it runs natively instead.
+    //The config is written directly, initialize_protocol needs upgradeable loader program data the test program doesn't have
+    pub async fn new() -> Self {
+        let mut program_test = if Path::new(PROGRAM_SO).exists() {
+            let mut program_test = ProgramTest::default();
+            let data = std::fs::read(PROGRAM_SO).unwrap();
+            program_test.add_account(options_program::ID, Account {
+                lamports: Rent::default().minimum_balance(data.len()),
+                data,
+                owner: bpf_loader::ID,
+                executable: true,
+                rent_epoch: 0,
+            });
+            program_test
+        } else {
+            let mut program_test = ProgramTest::new("options_program", options_program::ID, processor!(process_instruction));
+            program_test.prefer_bpf(false);
+            program_test
+        };
+
+        let admin = Keypair::new();
+        let (config_address, bump) = find_protocol_config_address();
+        let config = ProtocolConfig { admin: admin.pubkey(), pending_admin: Pubkey::default(), bump, role_grants: vec![] };
+        program_test.add_account(config_address, program_account(&config, 8 + ProtocolConfig::INIT_SPACE));
+
+        let asset_mint = Pubkey::new_unique();
+        let mint = spl_token::state::Mint {
+            mint_authority: None.into(),
+            supply: u64::MAX,
+            decimals: ASSET_DECIMALS,
+            is_initialized: true,
+            freeze_authority: None.into(),
+        };
+        program_test.add_account(asset_mint, packed_account(&mint, spl_token::ID));
+
+        let ctx = program_test.start_with_context().await;
+        let mut env = TestEnv { ctx, admin, asset_mint, price_update: Pubkey::new_unique(), now: START };
+
+        env.warp_to(START).await;
+        env.set_price(SPOT, 0, START);
+        let admin = env.admin.insecure_clone();
+        env.fund(&admin, 0);
+        env
+    }
+
+    pub async fn new_with_market(params: CreateMarketParams) -> Self {
+        let env = Self::new().await;
+        env.send(&env.admin, create_market(env.admin.pubkey(), env.asset_mint, spl_token::ID, params)).await.unwrap();
+        env
+    }
+
+    //Funded signer with an asset token account holding `tokens`
+    pub fn new_user(&mut self, tokens: u64) -> Keypair {
+        let user = Keypair::new();
+        self.fund(&user, tokens);
+        user
+    }
+
+    pub fn fund(&mut self, user: &Keypair, tokens: u64) {
+        self.ctx.set_account(
+            &user.pubkey(),
+            &Account::new(100 * LAMPORTS_PER_SOL, 0, &system_program::ID).into(),
+        );
+        self.set_token_balance(&user.pubkey(), tokens);
+    }
+
+    pub fn set_token_balance(&mut self, owner: &Pubkey, tokens: u64) {
+        let token_account = spl_token::state::Account {
+            mint: self.asset_mint,
+            owner: *owner,
+            amount: tokens,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        self.ctx.set_account(
+            &get_associated_token_address(owner, &self.asset_mint),
+            &packed_account(&token_account, spl_token::ID).into(),
+        );
+    }
+
+    //Pyth price update for the SOL/USD feed, price and confidence in usd scaled by 10^8
+    pub fn set_price(&mut self, price: i64, conf: u64, publish_time: i64) {
+        self.set_price_message(price, conf, -8, publish_time);
+    }
+
+    pub fn set_price_message(&mut self, price: i64, conf: u64, exponent: i32, publish_time: i64) {
+        let price_update = PriceUpdateV2 {
+            write_authority: Pubkey::default(),
+            verification_level: VerificationLevel::Full,
+            price_message: PriceFeedMessage {
+                feed_id: get_feed_id_from_hex(SOL_USD_FEED).unwrap(),
+                price,
+                conf,
+                exponent,
+                publish_time,
+                prev_publish_time: publish_time - 1,
+                ema_price: price,
+                ema_conf: conf,
+            },
+            posted_slot: 0,
+        };
+
+        let mut account = program_account(&price_update, PriceUpdateV2::LEN);
+        account.owner = pyth_solana_receiver_sdk::ID;
+        self.ctx.set_account(&self.price_update, &account.into());
+    }
+
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let mut clock: Clock = self.ctx.banks_client.get_sysvar().await.unwrap();
+        clock.unix_timestamp = unix_timestamp;
+        self.ctx.set_sysvar(&clock);
+        self.now = unix_timestamp;
+    }
+
+    //Moves the clock and publishes a fresh price at the new time
+    pub async fn warp_with_price(&mut self, unix_timestamp: i64, price: i64) {
+        self.warp_to(unix_timestamp).await;
+        self.set_price(price, 0, unix_timestamp);
+    }
+
+    //Signed by `signer` as the fee payer. Events emitted by a native program don't reach the transaction logs,
+    //so tests check account state instead
+    pub async fn send(&self, signer: &Keypair, ix: Instruction) -> Result<(), BanksClientError> {
+        let tx = self.transaction(signer, vec![ix]);
+        self.ctx.banks_client.process_transaction(tx).await
+    }
+
+    pub async fn simulate(&self, signer: &Keypair, ix: Instruction) -> Option<Vec<u8>> {
+        let tx = self.transaction(signer, vec![ix]);
+        let simulation = self.ctx.banks_client.simulate_transaction(tx).await.unwrap();
+        simulation.result.unwrap().unwrap();
+
+        simulation.simulation_details
+            .and_then(|details| details.return_data)
+            .map(|return_data| return_data.data)
+    }
+
+    fn transaction(&self, signer: &Keypair, instructions: Vec<Instruction>) -> Transaction {
+        //A distinct compute unit limit per transaction, so repeating an instruction in the same block
+        //doesn't produce an already processed duplicate
+        let nonce = NONCE.fetch_add(1, Ordering::Relaxed);
+        let mut all = vec![ComputeBudgetInstruction::set_compute_unit_limit(1_400_000 - nonce)];
+        all.extend(instructions);
+
+        Transaction::new_signed_with_payer(&all, Some(&signer.pubkey()), &[signer], self.ctx.last_blockhash)
+    }
+
+    pub async fn market(&self) -> Market {
+        self.anchor_account(find_market_address(MARKET_IX).0).await
+    }
+
+    pub async fn anchor_account<T: AccountDeserialize>(&self, address: Pubkey) -> T {
+        let account = self.ctx.banks_client.get_account(address).await.unwrap().expect("account not found");
+        T::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    pub async fn user_account(&self, owner: &Pubkey, page: u8) -> UserAccount {
+        let account = self.ctx.banks_client.get_account(find_user_account_address(owner, page).0).await.unwrap().unwrap();
+        decode_user_account(&account.data).unwrap()
+    }
+
+    pub async fn exists(&self, address: Pubkey) -> bool {
+        self.ctx.banks_client.get_account(address).await.unwrap().is_some()
+    }
+
+    pub async fn token_balance(&self, address: Pubkey) -> u64 {
+        let account = self.ctx.banks_client.get_account(address).await.unwrap().expect("token account not found");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    pub async fn user_balance(&self, owner: &Pubkey) -> u64 {
+        self.token_balance(get_associated_token_address(owner, &self.asset_mint)).await
+    }
+
+    pub async fn lp_balance(&self, owner: &Pubkey) -> u64 {
+        self.token_balance(get_associated_token_address(owner, &find_market_lp_mint_address(MARKET_IX).0)).await
+    }
+
+    //Instruction builders with this environment's mint, price feed and market
+
+    pub fn deposit(&self, user: &Keypair, amount: u64) -> Instruction {
+        market_deposit(user.pubkey(), self.asset_mint, spl_token::ID, DepositIx { amount, min_amount_out: 1, ix: MARKET_IX })
+    }
+
+    pub fn withdraw(&self, user: &Keypair, lp_tokens_to_burn: u64, min_amount_out: u64) -> Instruction {
+        market_withdraw(user.pubkey(), self.asset_mint, spl_token::ID, WithdrawParams { lp_tokens_to_burn, min_amount_out, ix: MARKET_IX })
+    }
+
+    pub fn buy(&self, user: &Keypair, params: BuyOptionParams) -> Instruction {
+        buy(user.pubkey(), self.asset_mint, self.price_update, spl_token::ID, params)
+    }
+
+    pub fn buy_at_strike(&self, user: &Keypair, params: BuyOptionAtStrikeParams) -> Instruction {
+        buy_at_strike(user.pubkey(), self.asset_mint, self.price_update, spl_token::ID, params)
+    }
+
+    pub fn exercise(&self, user: &Keypair, option_id: u8, quantity: u64, settlement: Option<Pubkey>) -> Instruction {
+        exercise(
+            user.pubkey(),
+            self.asset_mint,
+            self.price_update,
+            settlement,
+            spl_token::ID,
+            ExerciseOptionParams { market_ix: MARKET_IX, page: 0, option_id, quantity },
+        )
+    }
+
+    pub fn sell_back(&self, user: &Keypair, option_id: u8, quantity: u64, min_amount_out: u64) -> Instruction {
+        sell_back(user.pubkey(), self.asset_mint, self.price_update, spl_token::ID, SellBackParams { market_ix: MARKET_IX, page: 0, option_id, quantity, min_amount_out })
+    }
+
+    pub fn buy_tokenized(&self, user: &Keypair, params: BuyTokenizedParams) -> Instruction {
+        buy_tokenized(user.pubkey(), self.asset_mint, self.price_update, spl_token::ID, params)
+    }
+
+    pub fn exercise_tokenized(&self, user: &Keypair, params: ExerciseTokenizedParams) -> Instruction {
+        exercise_tokenized(user.pubkey(), self.asset_mint, self.price_update, None, spl_token::ID, params)
+    }
+
+    pub fn record_settlement(&self, signer: &Keypair, expiry: i64) -> Instruction {
+        record_settlement(signer.pubkey(), self.price_update, RecordSettlementParams { market_ix: MARKET_IX, expiry })
+    }
+
+    pub fn update_vol(&self, signer: &Keypair, vol_surface_bps: [[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES]) -> Instruction {
+        update_market_vol(signer.pubkey(), UpdateMarketVolParams { ix: MARKET_IX, vol_surface_bps })
+    }
+
+    pub fn set_status(&self, signer: &Keypair, status: u8) -> Instruction {
+        set_market_status(signer.pubkey(), SetMarketStatusParams { ix: MARKET_IX, status })
+    }
+
+    //Account page 0 and a liquidity provider with `liquidity` deposited
+    pub async fn setup_trading(&mut self, liquidity: u64) -> (Keypair, Keypair) {
+        let lp = self.new_user(liquidity);
+        self.send(&lp, self.deposit(&lp, liquidity)).await.unwrap();
+
+        let trader = self.new_user(100 * LAMPORTS_PER_SOL);
+        self.send(&trader, create_account(trader.pubkey(), AccountCreateParams { page: 0 })).await.unwrap();
+
+        (lp, trader)
+    }
+}
+
+//Same market as the TypeScript suite: 1 SOL contracts, Black-Scholes pricing, American exercise
+pub fn market_params() -> CreateMarketParams {
+    CreateMarketParams {
+        fee: 50,
+        name: "wSOL market".to_string(),
+        ix: MARKET_IX,
+        price_feed: SOL_USD_FEED.to_string(),
+        vol_surface_bps: vol_surface(),
+        max_vol_age_secs: 3600,
+        min_volatility_bps: 1000,
+        max_volatility_bps: 30000,
+        max_vol_change_bps: 5000,
+        keeper_bounty_bps: 50,
+        pricing_model: PricingModel::BlackScholes,
+        max_price_age_buy_secs: 60,
+        max_price_age_exercise_secs: 60,
+        max_price_conf_bps: 100,
+        exercise_use_ema: false,
+        strike_tick_size: 100_000_000,
+        min_strike_moneyness_bps: 8000,
+        max_strike_moneyness_bps: 12000,
+        expiry_series: EXPIRY_SERIES_ALL,
+        expiry_hour_utc: 8,
+        min_time_to_expiry_secs: 900,
+        exercise_style: ExerciseStyle::American,
+        sell_back_spread_bps: 200,
+        contract_size: LAMPORTS_PER_SOL,
+    }
+}
+
+pub fn vol_surface() -> [[u32; VOL_SURFACE_STRIKES]; VOL_SURFACE_EXPIRIES] {
+    [
+        [7000, 6800, 6500, 6200, 6000, 5900, 5800, 5800, 5800],
+        [8000, 7800, 7500, 7200, 7000, 6900, 6800, 6800, 6800],
+        [11000, 10500, 10000, 9500, 9000, 8800, 8700, 8700, 8700],
+        [10000, 9500, 9000, 8500, 8000, 7800, 7700, 7700, 7700],
+        [6000, 5800, 5500, 5200, 5000, 4900, 4800, 4800, 4800],
+    ]
+}
+
+pub fn buy_params(option: OptionType, spot_deviation: SpotDeviation, quantity: u64) -> BuyOptionParams {
+    BuyOptionParams {
+        market_ix: MARKET_IX,
+        page: 0,
+        option,
+        spot_deviation,
+        expiry_setting: ExpirySetting::Daily { offset: 0 },
+        quantity,
+        max_premium_tokens: u64::MAX,
+        max_spot_deviation: None,
+    }
+}
+
+pub fn quote_params(option: OptionType, strike: StrikeSetting, quantity: u64) -> QuoteParams {
+    QuoteParams {
+        market_ix: MARKET_IX,
+        option,
+        strike,
+        expiry_setting: ExpirySetting::Daily { offset: 0 },
+        quantity,
+    }
+}
+
+pub fn first_daily_expiry() -> i64 {
+    START + 8 * 3600
+}
+
+pub fn assert_custom_error<T: std::fmt::Debug>(result: Result<T, BanksClientError>, expected: CustomError) {
+    let code = u32::from(expected);
+    match result.expect_err("transaction should fail").unwrap() {
+        TransactionError::InstructionError(_, InstructionError::Custom(actual)) => {
+            assert_eq!(actual, code, "expected {expected:?} ({code}), got {actual}")
+        },
+        other => panic!("expected {expected:?} ({code}), got {other:?}"),
+    }
+}
+
+fn program_account<T: AccountSerialize>(data: &T, space: usize) -> Account {
+    let mut bytes = Vec::with_capacity(space);
+    data.try_serialize(&mut bytes).unwrap();
+    bytes.resize(space, 0);
+
+    Account {
+        lamports: Rent::default().minimum_balance(space),
+        data: bytes,
+        owner: options_program::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn packed_account<T: Pack>(state: &T, owner: Pubkey) -> Account {
+    let mut data = vec![0; T::LEN];
+    state.pack_into_slice(&mut data);
+
+    Account { lamports: Rent::default().minimum_balance(T::LEN), data, owner, executable: false, rent_epoch: 0 }
+}
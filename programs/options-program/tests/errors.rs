@@ -0,0 +1,394 @@
+//One test per CustomError the program raises, each driving a transaction into that error.
+//Underflow and NotImplemented are declared but never returned by any instruction.
+
+mod common;
+
+use anchor_spl::token::spl_token;
+use common::*;
+use options_program::common::*;
+use options_program::errors::CustomError;
+use options_program::state::market::*;
+use options_program_sdk::{instructions::*, pda::*};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::{Keypair, Signer}};
+
+const SOL: u64 = LAMPORTS_PER_SOL;
+
+async fn trading_env(params: CreateMarketParams) -> (TestEnv, Keypair, Keypair) {
+    let mut env = TestEnv::new_with_market(params).await;
+    let (lp, trader) = env.setup_trading(100 * SOL).await;
+    (env, lp, trader)
+}
+
+fn call(quantity: u64) -> BuyOptionParams {
+    buy_params(OptionType::CALL, SpotDeviation::P5, quantity)
+}
+
+fn at_strike(strike_price: u64) -> BuyOptionAtStrikeParams {
+    BuyOptionAtStrikeParams {
+        market_ix: MARKET_IX,
+        page: 0,
+        option: OptionType::CALL,
+        strike_price,
+        expiry_setting: ExpirySetting::Daily { offset: 0 },
+        quantity: 1,
+        max_premium_tokens: u64::MAX,
+        max_spot_deviation: None,
+    }
+}
+
+#[tokio::test]
+async fn unauthorized() {
+    let mut env = TestEnv::new().await;
+    let outsider = env.new_user(0);
+    let market = |ix| CreateMarketParams { ix, ..market_params() };
+
+    let result = env.send(&outsider, create_market(outsider.pubkey(), env.asset_mint, spl_token::ID, market(MARKET_IX))).await;
+    assert_custom_error(result, CustomError::Unauthorized);
+    env.send(&env.admin, create_market(env.admin.pubkey(), env.asset_mint, spl_token::ID, market(MARKET_IX))).await.unwrap();
+
+    let ixs = [
+        env.update_vol(&outsider, vol_surface()),
+        env.set_status(&outsider, MARKET_PAUSE_BUY),
+        withdraw_fees(outsider.pubkey(), env.asset_mint, spl_token::ID, WithdrawFeesParams { ix: MARKET_IX }),
+        close_market(outsider.pubkey(), env.asset_mint, spl_token::ID, CloseMarketParams { ix: MARKET_IX }),
+        grant_role(outsider.pubkey(), GrantRoleParams { authority: outsider.pubkey(), role: Role::Pauser }),
+        revoke_role(outsider.pubkey(), RevokeRoleParams { authority: env.admin.pubkey(), role: Role::Pauser }),
+        propose_admin(outsider.pubkey(), ProposeAdminParams { new_admin: outsider.pubkey() }),
+        accept_admin(outsider.pubkey()),
+    ];
+    for ix in ixs {
+        assert_custom_error(env.send(&outsider, ix).await, CustomError::Unauthorized);
+    }
+
+    //A role only opens its own instructions
+    env.send(&env.admin, grant_role(env.admin.pubkey(), GrantRoleParams { authority: outsider.pubkey(), role: Role::VolKeeper })).await.unwrap();
+    env.send(&outsider, env.update_vol(&outsider, vol_surface())).await.unwrap();
+    assert_custom_error(env.send(&outsider, env.set_status(&outsider, MARKET_PAUSE_BUY)).await, CustomError::Unauthorized);
+
+    //initialize_protocol checks the upgrade authority first, which the natively loaded program doesn't have
+    let result = env.send(&env.admin, initialize_protocol(env.admin.pubkey(), InitializeProtocolParams { admin: env.admin.pubkey() })).await;
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn slippage_exceeded() {
+    let (env, lp, trader) = trading_env(market_params()).await;
+
+    let result = env.send(&trader, env.buy(&trader, BuyOptionParams { max_premium_tokens: 1, ..call(1) })).await;
+    assert_custom_error(result, CustomError::SlippageExceeded);
+
+    //Oracle moved 1% off the quote, 0.5% allowed
+    let max_spot_deviation = Some(SpotSlippage { quoted_spot_price: 15_150_000_000, max_deviation_bps: 50 });
+    let result = env.send(&trader, env.buy(&trader, BuyOptionParams { max_spot_deviation, ..call(1) })).await;
+    assert_custom_error(result, CustomError::SlippageExceeded);
+
+    let deposit = market_deposit(lp.pubkey(), env.asset_mint, spl_token::ID, DepositIx { amount: SOL, min_amount_out: u64::MAX, ix: MARKET_IX });
+    assert_custom_error(env.send(&lp, deposit).await, CustomError::SlippageExceeded);
+
+    let lp_tokens = env.lp_balance(&lp.pubkey()).await;
+    assert_custom_error(env.send(&lp, env.withdraw(&lp, lp_tokens, 100 * SOL + 1)).await, CustomError::SlippageExceeded);
+}
+
+#[tokio::test]
+async fn invalid_amount() {
+    let (env, lp, _) = trading_env(market_params()).await;
+    assert_custom_error(env.send(&lp, env.deposit(&lp, 0)).await, CustomError::InvalidAmount);
+
+    let env = TestEnv::new().await;
+    for params in [
+        CreateMarketParams { keeper_bounty_bps: 101, ..market_params() },
+        CreateMarketParams { max_price_age_buy_secs: 0, ..market_params() },
+        CreateMarketParams { max_price_age_exercise_secs: 3_601, ..market_params() },
+        CreateMarketParams { max_price_conf_bps: 0, ..market_params() },
+        CreateMarketParams { max_price_conf_bps: 10_001, ..market_params() },
+    ] {
+        let result = env.send(&env.admin, create_market(env.admin.pubkey(), env.asset_mint, spl_token::ID, params)).await;
+        assert_custom_error(result, CustomError::InvalidAmount);
+    }
+}
+
+#[tokio::test]
+async fn invalid_quantity() {
+    let (env, _, trader) = trading_env(market_params()).await;
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(0))).await, CustomError::InvalidQuantity);
+
+    env.send(&trader, env.buy(&trader, call(2))).await.unwrap();
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 3, None)).await, CustomError::InvalidQuantity);
+}
+
+#[tokio::test]
+async fn dust_amount() {
+    let (mut env, _, _) = trading_env(market_params()).await;
+
+    //1 base unit against a 100 SOL pool rounds to 0 LP tokens
+    let small_lp = env.new_user(1);
+    assert_custom_error(env.send(&small_lp, env.deposit(&small_lp, 1)).await, CustomError::DustAmount);
+}
+
+#[tokio::test]
+async fn overflow() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+
+    //Collateral of u64::MAX contracts at $2000 doesn't fit u128
+    env.set_price(200_000_000_000, 0, START);
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(u64::MAX))).await, CustomError::Overflow);
+}
+
+#[tokio::test]
+async fn orders_limit_exceeded() {
+    let (env, _, trader) = trading_env(market_params()).await;
+
+    for _ in 0..32 {
+        env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+    }
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::OrdersLimitExceeded);
+}
+
+#[tokio::test]
+async fn invalid_expiry() {
+    let (env, _, trader) = trading_env(CreateMarketParams { expiry_series: EXPIRY_SERIES_DAILY | EXPIRY_SERIES_CUSTOM, ..market_params() }).await;
+
+    //Closer than min_time_to_expiry
+    let params = BuyOptionParams { expiry_setting: ExpirySetting::Custom { expiry_stamp: START + 60 }, ..call(1) };
+    assert_custom_error(env.send(&trader, env.buy(&trader, params)).await, CustomError::InvalidExpiry);
+
+    //Series not listed by the market
+    let params = BuyOptionParams { expiry_setting: ExpirySetting::Weekly { offset: 0 }, ..call(1) };
+    assert_custom_error(env.send(&trader, env.buy(&trader, params)).await, CustomError::InvalidExpiry);
+}
+
+#[tokio::test]
+async fn insufficient_colateral() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+    let trader = env.new_user(100 * SOL);
+    env.send(&trader, create_account(trader.pubkey(), AccountCreateParams { page: 0 })).await.unwrap();
+
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::InsufficientColateral);
+}
+
+#[tokio::test]
+async fn invalid_price_feed() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+
+    //10^-30 exponent can't be rescaled to 10^-8
+    env.set_price_message(SPOT, 0, -30, START);
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::InvalidPriceFeed);
+}
+
+#[tokio::test]
+async fn exercise_is_overdue() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+
+    env.warp_with_price(first_daily_expiry() + 301, SPOT).await;
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 1, None)).await, CustomError::ExerciseIsOverdue);
+}
+
+#[tokio::test]
+async fn exercise_too_early() {
+    let (env, _, trader) = trading_env(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 1, None)).await, CustomError::ExerciseTooEarly);
+    assert_custom_error(env.send(&trader, env.record_settlement(&trader, first_daily_expiry())).await, CustomError::ExerciseTooEarly);
+}
+
+#[tokio::test]
+async fn insufficient_shares() {
+    let (env, lp, _) = trading_env(market_params()).await;
+
+    let lp_tokens = env.lp_balance(&lp.pubkey()).await;
+    assert_custom_error(env.send(&lp, env.withdraw(&lp, lp_tokens + 1, 1)).await, CustomError::InsufficientShares);
+}
+
+#[tokio::test]
+async fn invalid_state() {
+    let (env, _, trader) = trading_env(market_params()).await;
+
+    assert_custom_error(env.send(&env.admin, env.set_status(&env.admin, 1 << 7)).await, CustomError::InvalidState);
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 1, None)).await, CustomError::InvalidState);
+    assert_custom_error(env.send(&trader, env.exercise(&trader, u8::MAX, 1, None)).await, CustomError::InvalidState);
+    assert_custom_error(env.send(&trader, env.sell_back(&trader, u8::MAX, 1, 0)).await, CustomError::InvalidState);
+
+    //Slot ids past the page are rejected, not a panic
+    let keeper = exercise_for(env.admin.pubkey(), trader.pubkey(), env.asset_mint, env.price_update, None, spl_token::ID, ExerciseForParams { market_ix: MARKET_IX, page: 0, option_id: u8::MAX });
+    assert_custom_error(env.send(&env.admin, keeper).await, CustomError::InvalidState);
+
+    //American markets have no settlement price
+    let result = env.send(&trader, env.record_settlement(&trader, first_daily_expiry())).await;
+    assert_custom_error(result, CustomError::InvalidState);
+}
+
+#[tokio::test]
+async fn premium_calc_error() {
+    //A one base unit contract is worth less than 10^-8 usd
+    let (env, _, trader) = trading_env(CreateMarketParams { contract_size: 1, ..market_params() }).await;
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::PremiumCalcError);
+}
+
+#[tokio::test]
+async fn invalid_prices() {
+    let (mut env, _, trader) = trading_env(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+
+    //Fresh, but published too long after the expiry to be the settlement price
+    let expiry = first_daily_expiry();
+    env.warp_to(expiry + 60).await;
+    env.set_price(SPOT, 0, expiry + 11);
+    assert_custom_error(env.send(&trader, env.record_settlement(&trader, expiry)).await, CustomError::InvalidPrices);
+
+    //Published before the expiry is older than the settlement allows
+    env.set_price(SPOT, 0, expiry - 1);
+    assert!(env.send(&trader, env.record_settlement(&trader, expiry)).await.is_err());
+}
+
+#[tokio::test]
+async fn cannot_withdraw() {
+    let (env, lp, _) = trading_env(market_params()).await;
+
+    //One LP token out of 10^14 redeems nothing
+    assert_custom_error(env.send(&lp, env.withdraw(&lp, 1, 1)).await, CustomError::CannotWithdraw);
+}
+
+#[tokio::test]
+async fn invalid_strike_price() {
+    let (env, _, trader) = trading_env(market_params()).await;
+    assert_custom_error(env.send(&trader, env.buy_at_strike(&trader, at_strike(15_050_000_000))).await, CustomError::InvalidStrikePrice);
+    let quote = quote_option(env.price_update, quote_params(OptionType::CALL, StrikeSetting::Price { strike_price: 15_050_000_000 }, 1));
+    assert_custom_error(env.send(&trader, quote).await, CustomError::InvalidStrikePrice);
+
+    let params = CreateMarketParams { ix: MARKET_IX + 1, strike_tick_size: 0, ..market_params() };
+    let result = env.send(&env.admin, create_market(env.admin.pubkey(), env.asset_mint, spl_token::ID, params)).await;
+    assert_custom_error(result, CustomError::InvalidStrikePrice);
+}
+
+#[tokio::test]
+async fn invalid_volatility() {
+    let env = TestEnv::new_with_market(market_params()).await;
+
+    let mut surface = vol_surface();
+    surface[2][4] = 500;
+    assert_custom_error(env.send(&env.admin, env.update_vol(&env.admin, surface)).await, CustomError::InvalidVolatility);
+}
+
+#[tokio::test]
+async fn volatility_staled() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+
+    env.warp_with_price(START + 3601, SPOT).await;
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::VolatilityStaled);
+
+    //A vol update makes the market tradable again
+    env.send(&env.admin, env.update_vol(&env.admin, vol_surface())).await.unwrap();
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+}
+
+#[tokio::test]
+async fn invalid_spot_price() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+
+    env.set_price(-1, 0, START);
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::InvalidSpotPrice);
+}
+
+#[tokio::test]
+async fn role_limit_exceeded() {
+    let env = TestEnv::new().await;
+    let grant = |authority| grant_role(env.admin.pubkey(), GrantRoleParams { authority, role: Role::VolKeeper });
+
+    for _ in 0..16 {
+        env.send(&env.admin, grant(Keypair::new().pubkey())).await.unwrap();
+    }
+    assert_custom_error(env.send(&env.admin, grant(Keypair::new().pubkey())).await, CustomError::RoleLimitExceeded);
+}
+
+#[tokio::test]
+async fn market_paused() {
+    let (env, lp, trader) = trading_env(market_params()).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+
+    env.send(&env.admin, env.set_status(&env.admin, MARKET_PAUSE_ALL)).await.unwrap();
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::MarketPaused);
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 1, None)).await, CustomError::MarketPaused);
+    assert_custom_error(env.send(&lp, env.deposit(&lp, SOL)).await, CustomError::MarketPaused);
+    assert_custom_error(env.send(&lp, env.withdraw(&lp, SOL, 1)).await, CustomError::MarketPaused);
+}
+
+#[tokio::test]
+async fn volatility_change_too_large() {
+    let env = TestEnv::new_with_market(market_params()).await;
+
+    let mut surface = vol_surface();
+    surface[0][0] += 5001;
+    assert_custom_error(env.send(&env.admin, env.update_vol(&env.admin, surface)).await, CustomError::VolatilityChangeTooLarge);
+}
+
+#[tokio::test]
+async fn price_confidence_too_wide() {
+    let (mut env, _, trader) = trading_env(market_params()).await;
+
+    //2% confidence, the market allows 1%
+    env.set_price(SPOT, 300_000_000, START);
+    assert_custom_error(env.send(&trader, env.buy(&trader, call(1))).await, CustomError::PriceConfidenceTooWide);
+}
+
+#[tokio::test]
+async fn strike_out_of_range() {
+    let (env, _, trader) = trading_env(market_params()).await;
+
+    //$100 is 67% of spot, below the 80% band
+    assert_custom_error(env.send(&trader, env.buy_at_strike(&trader, at_strike(10_000_000_000))).await, CustomError::StrikeOutOfRange);
+}
+
+#[tokio::test]
+async fn settlement_not_recorded() {
+    let (mut env, _, trader) = trading_env(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+
+    env.warp_with_price(first_daily_expiry() + 60, SPOT).await;
+    assert_custom_error(env.send(&trader, env.exercise(&trader, 0, 1, None)).await, CustomError::SettlementNotRecorded);
+}
+
+#[tokio::test]
+async fn account_has_open_positions() {
+    let (env, _, trader) = trading_env(market_params()).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+
+    let result = env.send(&trader, close_account(trader.pubkey(), &[MARKET_IX], AccountCloseParams { page: 0 })).await;
+    assert_custom_error(result, CustomError::AccountHasOpenPositions);
+    assert!(env.exists(find_user_account_address(&trader.pubkey(), 0).0).await);
+
+    //European options stay open for the whole European exercise window
+    let (mut env, _, trader) = trading_env(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+    env.warp_with_price(first_daily_expiry() + 3600, SPOT).await;
+
+    let result = env.send(&trader, close_account(trader.pubkey(), &[MARKET_IX], AccountCloseParams { page: 0 })).await;
+    assert_custom_error(result, CustomError::AccountHasOpenPositions);
+}
+
+#[tokio::test]
+async fn market_not_empty() {
+    let (mut env, lp, trader) = trading_env(market_params()).await;
+    let manager = env.new_user(0);
+    env.send(&env.admin, grant_role(env.admin.pubkey(), GrantRoleParams { authority: manager.pubkey(), role: Role::MarketManager })).await.unwrap();
+    let close = close_market(manager.pubkey(), env.asset_mint, spl_token::ID, CloseMarketParams { ix: MARKET_IX });
+
+    //LP deposits and collateral stay with their owners
+    env.send(&trader, env.buy(&trader, call(1))).await.unwrap();
+    assert_custom_error(env.send(&manager, close.clone()).await, CustomError::MarketNotEmpty);
+
+    //With the option lapsed and the LP out, the fee vault still needs the FeeCollector
+    env.warp_with_price(first_daily_expiry() + 3600, SPOT).await;
+    env.send(&trader, settle_expired(trader.pubkey(), trader.pubkey(), SettleExpiredParams { market_ix: MARKET_IX, page: 0 })).await.unwrap();
+    env.send(&lp, env.withdraw(&lp, env.lp_balance(&lp.pubkey()).await, 1)).await.unwrap();
+    let fees_vault = find_protocol_fees_vault_address(MARKET_IX).0;
+    let fees = env.token_balance(fees_vault).await;
+    assert!(fees > 0);
+    assert_custom_error(env.send(&manager, close.clone()).await, CustomError::MarketNotEmpty);
+    assert_eq!(env.token_balance(fees_vault).await, fees);
+    assert_eq!(env.user_balance(&manager.pubkey()).await, 0);
+
+    env.send(&env.admin, withdraw_fees(env.admin.pubkey(), env.asset_mint, spl_token::ID, WithdrawFeesParams { ix: MARKET_IX })).await.unwrap();
+    env.send(&manager, close).await.unwrap();
+    assert!(!env.exists(find_market_address(MARKET_IX).0).await);
+}
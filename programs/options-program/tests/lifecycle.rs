@@ -0,0 +1,290 @@
+mod common;
+
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account;
+use anchor_spl::token::spl_token;
+use common::*;
+use options_program::common::{ExerciseStyle, ExpirySetting, OptionType, SpotDeviation, StrikeSetting};
+use options_program::constants::EUROPEAN_EXERCISE_WINDOW_SECS;
+use options_program::errors::CustomError;
+use options_program::state::option_series::OptionSeries;
+use options_program::state::settlement::Settlement;
+use options_program_sdk::{instructions::*, pda::*};
+use solana_sdk::{native_token::LAMPORTS_PER_SOL, signature::Signer};
+
+const SOL: u64 = LAMPORTS_PER_SOL;
+
+#[tokio::test]
+async fn american_option_lifecycle() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+
+    let market = env.market().await;
+    assert_eq!(market.asset_mint, env.asset_mint);
+    assert_eq!(market.asset_decimals, ASSET_DECIMALS);
+    assert_eq!(market.vol_last_updated, START);
+
+    //LP deposit, the first depositor gets 1000 LP tokens per base unit
+    let (lp, trader) = env.setup_trading(100 * SOL).await;
+    assert_eq!(env.lp_balance(&lp.pubkey()).await, 100 * SOL * 1_000);
+    assert_eq!(env.token_balance(find_market_vault_address(MARKET_IX).0).await, 100 * SOL);
+    assert_eq!(env.market().await.reserve_supply, 100 * SOL);
+
+    //Quote, then buy the same 5% OTM call
+    let return_data = env.simulate(&trader, quote_option(env.price_update, quote_params(OptionType::CALL, StrikeSetting::SpotDeviation { deviation: SpotDeviation::P5 }, 2))).await;
+    let quote = decode_quote(&return_data.expect("quote return data")).unwrap();
+    assert_eq!(quote.strike_price_usd, 15_800_000_000);
+    assert_eq!(quote.expiry, first_daily_expiry());
+    assert_eq!(quote.spot_price_usd, SPOT as u64);
+
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::CALL, SpotDeviation::P5, 2))).await.unwrap();
+    assert_eq!(env.user_balance(&trader.pubkey()).await, 100 * SOL - quote.premium_tokens);
+    assert_eq!(env.token_balance(find_protocol_fees_vault_address(MARKET_IX).0).await, quote.fee_tokens);
+
+    let market = env.market().await;
+    assert_eq!(market.premiums, quote.premium_tokens - quote.fee_tokens);
+    assert_eq!(market.committed_reserve, quote.collateral_tokens);
+
+    let option = env.user_account(&trader.pubkey(), 0).await.options[0];
+    assert_eq!((option.strike_price, option.quantity, option.expiry), (15_800_000_000, 2, first_daily_expiry()));
+    assert_eq!((option.premium, option.max_potential_payout_in_tokens), (quote.premium_tokens, quote.collateral_tokens));
+
+    //Spot rallies to $170, the call pays (170 - 158) * 2 contracts in SOL
+    env.warp_with_price(START + 3600, 17_000_000_000).await;
+    let payout = 12 * 100_000_000 * 2 * SOL / 17_000_000_000;
+    assert!(payout < quote.collateral_tokens);
+
+    env.send(&trader, env.exercise(&trader, 0, 2, None)).await.unwrap();
+    assert_eq!(env.user_balance(&trader.pubkey()).await, 100 * SOL - quote.premium_tokens + payout);
+    assert!(!env.user_account(&trader.pubkey(), 0).await.options[0].is_initialized());
+
+    let market = env.market().await;
+    assert_eq!(market.committed_reserve, 0);
+    assert_eq!(market.reserve_supply + market.premiums, 100 * SOL + quote.premium_tokens - quote.fee_tokens - payout);
+
+    //Protocol fees go to the fee collector
+    let fees_vault = find_protocol_fees_vault_address(MARKET_IX).0;
+    env.send(&env.admin, withdraw_fees(env.admin.pubkey(), env.asset_mint, spl_token::ID, WithdrawFeesParams { ix: MARKET_IX })).await.unwrap();
+    assert_eq!(env.user_balance(&env.admin.pubkey()).await, quote.fee_tokens);
+    assert_eq!(env.token_balance(fees_vault).await, 0);
+
+    //The only LP redeems the whole pool
+    let lp_tokens = env.lp_balance(&lp.pubkey()).await;
+    let pool_value = market.reserve_supply + market.premiums;
+    env.send(&lp, env.withdraw(&lp, lp_tokens, pool_value)).await.unwrap();
+    assert_eq!(env.user_balance(&lp.pubkey()).await, pool_value);
+    assert_eq!(env.lp_balance(&lp.pubkey()).await, 0);
+    assert_eq!(env.token_balance(find_market_vault_address(MARKET_IX).0).await, 0);
+    assert_eq!(env.market().await.lp_minted, 0);
+
+    //Empty account page and market are closed
+    let account_address = find_user_account_address(&trader.pubkey(), 0).0;
+    env.send(&trader, close_account(trader.pubkey(), &[], AccountCloseParams { page: 0 })).await.unwrap();
+    assert!(!env.exists(account_address).await);
+
+    env.send(&env.admin, close_market(env.admin.pubkey(), env.asset_mint, spl_token::ID, CloseMarketParams { ix: MARKET_IX })).await.unwrap();
+    assert!(!env.exists(find_market_address(MARKET_IX).0).await);
+    assert!(!env.exists(find_market_vault_address(MARKET_IX).0).await);
+    assert!(!env.exists(fees_vault).await);
+}
+
+#[tokio::test]
+async fn strike_quote_matches_buy_at_strike() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+
+    let strike_price = 14_200_000_000;
+    let return_data = env.simulate(&trader, quote_option(env.price_update, quote_params(OptionType::PUT, StrikeSetting::Price { strike_price }, 3))).await;
+    let quote = decode_quote(&return_data.expect("quote return data")).unwrap();
+    assert_eq!((quote.strike_price_usd, quote.expiry), (strike_price, first_daily_expiry()));
+
+    env.send(&trader, env.buy_at_strike(&trader, BuyOptionAtStrikeParams {
+        market_ix: MARKET_IX,
+        page: 0,
+        option: OptionType::PUT,
+        strike_price,
+        expiry_setting: ExpirySetting::Daily { offset: 0 },
+        quantity: 3,
+        max_premium_tokens: quote.premium_tokens,
+        max_spot_deviation: None,
+    })).await.unwrap();
+
+    assert_eq!(env.user_balance(&trader.pubkey()).await, 100 * SOL - quote.premium_tokens);
+    assert_eq!(env.market().await.committed_reserve, quote.collateral_tokens);
+}
+
+#[tokio::test]
+async fn european_option_settles_at_recorded_price() {
+    let mut env = TestEnv::new_with_market(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+
+    //5% OTM put, strike $142.5 rounded to the $1 tick
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::PUT, SpotDeviation::N5, 1))).await.unwrap();
+    let option = env.user_account(&trader.pubkey(), 0).await.options[0];
+    assert_eq!(option.strike_price, 14_300_000_000);
+    let trader_balance = env.user_balance(&trader.pubkey()).await;
+
+    //Anyone records the price published at expiry
+    let expiry = first_daily_expiry();
+    env.warp_with_price(expiry, 13_000_000_000).await;
+    let keeper = env.new_user(0);
+    env.send(&keeper, env.record_settlement(&keeper, expiry)).await.unwrap();
+
+    let settlement_address = find_settlement_address(MARKET_IX, expiry).0;
+    let settlement: Settlement = env.anchor_account(settlement_address).await;
+    assert_eq!((settlement.price, settlement.publish_time), (13_000_000_000, expiry));
+
+    //The oracle moving afterwards doesn't change the payout, (143 - 130) / 130 SOL
+    env.warp_with_price(expiry + 60, 16_000_000_000).await;
+    env.send(&trader, env.exercise(&trader, 0, 1, Some(settlement_address))).await.unwrap();
+    assert_eq!(env.user_balance(&trader.pubkey()).await, trader_balance + SOL / 10);
+    assert_eq!(env.market().await.committed_reserve, 0);
+}
+
+#[tokio::test]
+async fn european_settlement_can_be_recorded_late() {
+    let mut env = TestEnv::new_with_market(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::PUT, SpotDeviation::N5, 1))).await.unwrap();
+    let trader_balance = env.user_balance(&trader.pubkey()).await;
+
+    //Nobody recorded the settlement within the American exercise window. The update published 5s after expiry
+    //is posted an hour later and still settles the expiry
+    let expiry = first_daily_expiry();
+    env.warp_to(expiry + 3600).await;
+    env.set_price(13_000_000_000, 0, expiry + 5);
+    let keeper = env.new_user(0);
+    env.send(&keeper, env.record_settlement(&keeper, expiry)).await.unwrap();
+
+    let settlement_address = find_settlement_address(MARKET_IX, expiry).0;
+    let settlement: Settlement = env.anchor_account(settlement_address).await;
+    assert_eq!((settlement.price, settlement.publish_time), (13_000_000_000, expiry + 5));
+
+    //The holder exercises hours after expiry at the recorded price, (143 - 130) / 130 SOL
+    env.warp_with_price(expiry + 6 * 3600, 16_000_000_000).await;
+    env.send(&trader, env.exercise(&trader, 0, 1, Some(settlement_address))).await.unwrap();
+    assert_eq!(env.user_balance(&trader.pubkey()).await, trader_balance + SOL / 10);
+    assert_eq!(env.market().await.committed_reserve, 0);
+}
+
+#[tokio::test]
+async fn unrecorded_european_option_lapses_after_the_exercise_window() {
+    let mut env = TestEnv::new_with_market(CreateMarketParams { exercise_style: ExerciseStyle::European, ..market_params() }).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::PUT, SpotDeviation::N5, 1))).await.unwrap();
+    let committed_reserve = env.market().await.committed_reserve;
+
+    //Past the American window the option is still live, its collateral stays committed
+    let expiry = first_daily_expiry();
+    let settle = settle_expired(trader.pubkey(), trader.pubkey(), SettleExpiredParams { market_ix: MARKET_IX, page: 0 });
+    env.warp_with_price(expiry + 3600, SPOT).await;
+    env.send(&trader, settle.clone()).await.unwrap();
+    assert_eq!(env.market().await.committed_reserve, committed_reserve);
+    assert!(env.user_account(&trader.pubkey(), 0).await.options[0].is_initialized());
+
+    //Once the European window closed, the settlement can't be recorded anymore and the option lapses
+    env.warp_with_price(expiry + EUROPEAN_EXERCISE_WINDOW_SECS + 1, SPOT).await;
+    env.set_price(SPOT, 0, expiry + 5);
+    assert_custom_error(env.send(&trader, env.record_settlement(&trader, expiry)).await, CustomError::ExerciseIsOverdue);
+
+    env.send(&trader, settle).await.unwrap();
+    assert_eq!(env.market().await.committed_reserve, 0);
+    assert!(!env.user_account(&trader.pubkey(), 0).await.options[0].is_initialized());
+}
+
+#[tokio::test]
+async fn tokenized_option_exercised_by_another_holder() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+
+    //3 put tokens struck at $142
+    let expiry = first_daily_expiry();
+    let strike_price = 14_200_000_000;
+    env.send(&trader, env.buy_tokenized(&trader, BuyTokenizedParams {
+        market_ix: MARKET_IX,
+        option: OptionType::PUT,
+        strike_price,
+        expiry_setting: ExpirySetting::Daily { offset: 0 },
+        expiry,
+        quantity: 3,
+        max_premium_tokens: u64::MAX,
+        max_spot_deviation: None,
+    })).await.unwrap();
+
+    let option_series = find_option_series_address(MARKET_IX, OptionType::PUT, strike_price, expiry).0;
+    let option_mint = find_option_mint_address(&option_series).0;
+    let series: OptionSeries = env.anchor_account(option_series).await;
+    assert_eq!(series.supply, 3);
+    assert_eq!(env.market().await.committed_reserve, series.collateral);
+
+    //2 tokens move to a wallet that never touched the market
+    let holder = env.new_user(0);
+    let trader_option_acc = get_associated_token_address(&trader.pubkey(), &option_mint);
+    let holder_option_acc = get_associated_token_address(&holder.pubkey(), &option_mint);
+    env.send(&holder, create_associated_token_account(&holder.pubkey(), &holder.pubkey(), &option_mint, &spl_token::ID)).await.unwrap();
+    env.send(&trader, spl_token::instruction::transfer_checked(
+        &spl_token::ID, &trader_option_acc, &option_mint, &holder_option_acc, &trader.pubkey(), &[], 2, 0,
+    ).unwrap()).await.unwrap();
+    assert_eq!(env.token_balance(holder_option_acc).await, 2);
+
+    //The new holder exercises at $130, (142 - 130) / 130 SOL per token
+    env.warp_with_price(expiry + 60, 13_000_000_000).await;
+    env.send(&holder, env.exercise_tokenized(&holder, ExerciseTokenizedParams {
+        market_ix: MARKET_IX,
+        option: OptionType::PUT,
+        strike_price,
+        expiry,
+        quantity: 2,
+    })).await.unwrap();
+    assert_eq!(env.user_balance(&holder.pubkey()).await, 2 * (12 * SOL / 130));
+    assert_eq!(env.token_balance(holder_option_acc).await, 0);
+
+    //The trader's last token lapses, its collateral goes back to LPs once
+    let series: OptionSeries = env.anchor_account(option_series).await;
+    assert_eq!(series.supply, 1);
+    assert_eq!(env.market().await.committed_reserve, series.collateral);
+
+    let settle = settle_expired_series(holder.pubkey(), SettleExpiredSeriesParams { market_ix: MARKET_IX, option: OptionType::PUT, strike_price, expiry });
+    assert_custom_error(env.send(&holder, settle.clone()).await, CustomError::InvalidState);
+
+    env.warp_with_price(expiry + 3600, SPOT).await;
+    env.send(&holder, settle.clone()).await.unwrap();
+    assert_eq!(env.market().await.committed_reserve, 0);
+    assert_eq!(env.anchor_account::<OptionSeries>(option_series).await.collateral, 0);
+    assert_custom_error(env.send(&holder, settle).await, CustomError::InvalidState);
+}
+
+#[tokio::test]
+async fn worthless_option_sells_back_for_nothing() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::CALL, SpotDeviation::P5, 1))).await.unwrap();
+    let trader_balance = env.user_balance(&trader.pubkey()).await;
+
+    //An hour before expiry spot is down to $100, the $158 call has no value left
+    env.warp_with_price(first_daily_expiry() - 3600, 10_000_000_000).await;
+    env.send(&env.admin, env.update_vol(&env.admin, vol_surface())).await.unwrap();
+    env.send(&trader, env.sell_back(&trader, 0, 1, 0)).await.unwrap();
+
+    assert_eq!(env.user_balance(&trader.pubkey()).await, trader_balance);
+    assert!(!env.user_account(&trader.pubkey(), 0).await.options[0].is_initialized());
+    assert_eq!(env.market().await.committed_reserve, 0);
+}
+
+#[tokio::test]
+async fn lapsed_option_collateral_is_released_on_account_close() {
+    let mut env = TestEnv::new_with_market(market_params()).await;
+    let (_lp, trader) = env.setup_trading(100 * SOL).await;
+
+    env.send(&trader, env.buy(&trader, buy_params(OptionType::CALL, SpotDeviation::P10, 3))).await.unwrap();
+    assert!(env.market().await.committed_reserve > 0);
+
+    //Expired out of the money and never exercised
+    env.warp_with_price(first_daily_expiry() + 301, SPOT).await;
+    let rent_payer_balance = env.ctx.banks_client.get_balance(trader.pubkey()).await.unwrap();
+    env.send(&trader, close_account(trader.pubkey(), &[MARKET_IX], AccountCloseParams { page: 0 })).await.unwrap();
+
+    assert_eq!(env.market().await.committed_reserve, 0);
+    assert!(!env.exists(find_user_account_address(&trader.pubkey(), 0).0).await);
+    assert!(env.ctx.banks_client.get_balance(trader.pubkey()).await.unwrap() > rent_payer_balance);
+    assert!(env.exists(get_associated_token_address(&trader.pubkey(), &env.asset_mint)).await);
+}
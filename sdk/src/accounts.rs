@@ -0,0 +1,21 @@
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use options_program::state::{market::Market, user_account::UserAccount};
+
+//Decoders for raw account data as returned by RPC, discriminator included
+
+pub fn decode_market(data: &[u8]) -> Result<Market> {
+    Market::try_deserialize(&mut &data[..])
+}
+
+//User accounts are zero copy, the data is read as is after the discriminator
+pub fn decode_user_account(data: &[u8]) -> Result<UserAccount> {
+    let disc_len = UserAccount::DISCRIMINATOR.len();
+    require!(data.len() >= disc_len, ErrorCode::AccountDiscriminatorNotFound);
+    require!(&data[..disc_len] == UserAccount::DISCRIMINATOR, ErrorCode::AccountDiscriminatorMismatch);
+
+    let body = data
+        .get(disc_len..disc_len + std::mem::size_of::<UserAccount>())
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+    bytemuck::try_pod_read_unaligned(body).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
@@ -0,0 +1,55 @@
+use anchor_lang::{AnchorDeserialize, Discriminator};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use options_program::state::event::*;
+
+//Anchor logs events as base64 "Program data: " lines
+const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+pub enum ProgramEvent {
+    MakerDeposit(MakerDepositEvent),
+    MakerWithdraw(MakerWithdrawEvent),
+    OptionBought(OptionBought),
+    OptionExercised(OptionExercised),
+    OptionExpired(OptionExpired),
+    OptionSoldBack(OptionSoldBack),
+    OptionTokensBought(OptionTokensBought),
+    OptionTokensExercised(OptionTokensExercised),
+    OptionSeriesExpired(OptionSeriesExpired),
+    SettlementRecorded(SettlementRecorded),
+}
+
+macro_rules! decode_as {
+    ($data:expr, $($variant:ident => $event:ty),+ $(,)?) => {
+        $(
+            if let Some(body) = $data.strip_prefix(<$event>::DISCRIMINATOR) {
+                return <$event>::try_from_slice(body).ok().map(ProgramEvent::$variant);
+            }
+        )+
+    };
+}
+
+//Decodes event data (discriminator included), None for data of other events or programs
+pub fn decode_event(data: &[u8]) -> Option<ProgramEvent> {
+    decode_as!(data,
+        MakerDeposit => MakerDepositEvent,
+        MakerWithdraw => MakerWithdrawEvent,
+        OptionBought => OptionBought,
+        OptionExercised => OptionExercised,
+        OptionExpired => OptionExpired,
+        OptionSoldBack => OptionSoldBack,
+        OptionTokensBought => OptionTokensBought,
+        OptionTokensExercised => OptionTokensExercised,
+        OptionSeriesExpired => OptionSeriesExpired,
+        SettlementRecorded => SettlementRecorded,
+    );
+    None
+}
+
+//Events of a transaction, from its log messages
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> Vec<ProgramEvent> {
+    logs.iter()
+        .filter_map(|line| line.as_ref().strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| decode_event(&data))
+        .collect()
+}
@@ -0,0 +1,397 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::{system_program, AnchorDeserialize, InstructionData};
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id};
+use options_program::{accounts, instruction, ID};
+
+use crate::pda::*;
+
+pub use options_program::instructions::admin::{
+    admin_propose::ProposeAdminParams, market_close::CloseMarketParams, market_create::CreateMarketParams,
+    market_set_status::SetMarketStatusParams, market_update_vol::UpdateMarketVolParams,
+    protocol_initialize::InitializeProtocolParams, role_grant::GrantRoleParams, role_revoke::RevokeRoleParams,
+    withdraw_fees::WithdrawFeesParams,
+};
+pub use options_program::instructions::makers::{market_deposit::DepositIx, market_withdraw::WithdrawParams};
+pub use options_program::instructions::takers::{
+    acc_close::AccountCloseParams, acc_create::AccountCreateParams,
+    buy::{BuyOptionAtStrikeParams, BuyOptionParams}, buy_tokenized::BuyTokenizedParams,
+    exercise::ExerciseOptionParams, exercise_for::ExerciseForParams, exercise_tokenized::ExerciseTokenizedParams,
+    quote::{OptionQuote, QuoteParams}, record_settlement::RecordSettlementParams, sell_back::SellBackParams,
+    settle_expired::SettleExpiredParams, settle_expired_series::SettleExpiredSeriesParams,
+};
+
+//Builders for every program instruction. PDAs are derived from the params, user and admin token accounts
+//are the associated token accounts of the signer (or owner) for the given token program.
+//`settlement` is only passed for European style markets, see `find_settlement_address`
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+fn ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+// --- Admin --- //
+
+pub fn initialize_protocol(signer: Pubkey, params: InitializeProtocolParams) -> Instruction {
+    build(
+        accounts::InitializeProtocol {
+            signer,
+            config: find_protocol_config_address().0,
+            program: ID,
+            program_data: find_program_data_address().0,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeProtocol { params },
+    )
+}
+
+pub fn propose_admin(signer: Pubkey, params: ProposeAdminParams) -> Instruction {
+    build(
+        accounts::ProposeAdmin { signer, config: find_protocol_config_address().0 },
+        instruction::ProposeAdmin { params },
+    )
+}
+
+pub fn accept_admin(signer: Pubkey) -> Instruction {
+    build(
+        accounts::AcceptAdmin { signer, config: find_protocol_config_address().0 },
+        instruction::AcceptAdmin {},
+    )
+}
+
+pub fn grant_role(signer: Pubkey, params: GrantRoleParams) -> Instruction {
+    build(
+        accounts::GrantRole { signer, config: find_protocol_config_address().0 },
+        instruction::GrantRole { params },
+    )
+}
+
+pub fn revoke_role(signer: Pubkey, params: RevokeRoleParams) -> Instruction {
+    build(
+        accounts::RevokeRole { signer, config: find_protocol_config_address().0 },
+        instruction::RevokeRole { params },
+    )
+}
+
+pub fn create_market(signer: Pubkey, asset_mint: Pubkey, token_program: Pubkey, params: CreateMarketParams) -> Instruction {
+    build(
+        accounts::CreateMarket {
+            signer,
+            config: find_protocol_config_address().0,
+            asset_mint,
+            lp_mint: find_market_lp_mint_address(params.ix).0,
+            market: find_market_address(params.ix).0,
+            market_vault: find_market_vault_address(params.ix).0,
+            protocol_fees_vault: find_protocol_fees_vault_address(params.ix).0,
+            token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CreateMarket { params },
+    )
+}
+
+pub fn update_market_vol(signer: Pubkey, params: UpdateMarketVolParams) -> Instruction {
+    build(
+        accounts::UpdateMarketVol {
+            signer,
+            config: find_protocol_config_address().0,
+            market: find_market_address(params.ix).0,
+            system_program: system_program::ID,
+        },
+        instruction::UpdateMarketVol { params },
+    )
+}
+
+pub fn withdraw_fees(admin: Pubkey, asset_mint: Pubkey, token_program: Pubkey, params: WithdrawFeesParams) -> Instruction {
+    build(
+        accounts::WithdrawFees {
+            admin,
+            config: find_protocol_config_address().0,
+            admin_token_acc: ata(&admin, &asset_mint, &token_program),
+            asset_mint,
+            protocol_fees_vault: find_protocol_fees_vault_address(params.ix).0,
+            token_program,
+            system_program: system_program::ID,
+        },
+        instruction::WithdrawFees { params },
+    )
+}
+
+pub fn close_market(admin: Pubkey, asset_mint: Pubkey, token_program: Pubkey, params: CloseMarketParams) -> Instruction {
+    build(
+        accounts::CloseMarket {
+            admin,
+            config: find_protocol_config_address().0,
+            asset_mint,
+            admin_asset_ata: ata(&admin, &asset_mint, &token_program),
+            lp_mint: find_market_lp_mint_address(params.ix).0,
+            market: find_market_address(params.ix).0,
+            market_vault: find_market_vault_address(params.ix).0,
+            protocol_fees_vault: find_protocol_fees_vault_address(params.ix).0,
+            token_program,
+            system_program: system_program::ID,
+        },
+        instruction::CloseMarket { params },
+    )
+}
+
+pub fn set_market_status(signer: Pubkey, params: SetMarketStatusParams) -> Instruction {
+    build(
+        accounts::SetMarketStatus {
+            signer,
+            config: find_protocol_config_address().0,
+            market: find_market_address(params.ix).0,
+        },
+        instruction::SetMarketStatus { params },
+    )
+}
+
+// --- Takers --- //
+
+pub fn create_account(signer: Pubkey, params: AccountCreateParams) -> Instruction {
+    build(
+        accounts::AccountCreate {
+            signer,
+            account: find_user_account_address(&signer, params.page).0,
+            system_program: system_program::ID,
+        },
+        instruction::CreateAccount { params },
+    )
+}
+
+//Markets of lapsed options still in the page are passed as remaining accounts, so they get settled on close
+pub fn close_account(signer: Pubkey, lapsed_markets: &[u16], params: AccountCloseParams) -> Instruction {
+    let mut ix = build(
+        accounts::AccountClose {
+            signer,
+            account: find_user_account_address(&signer, params.page).0,
+            system_program: system_program::ID,
+        },
+        instruction::CloseAccount { params },
+    );
+    ix.accounts.extend(lapsed_markets.iter().map(|market_ix| AccountMeta::new(find_market_address(*market_ix).0, false)));
+    ix
+}
+
+fn buy_accounts(signer: Pubkey, market_ix: u16, page: u8, asset_mint: Pubkey, price_update: Pubkey, token_program: Pubkey) -> accounts::BuyOption {
+    accounts::BuyOption {
+        signer,
+        account: find_user_account_address(&signer, page).0,
+        user_token_acc: ata(&signer, &asset_mint, &token_program),
+        market: find_market_address(market_ix).0,
+        market_vault: find_market_vault_address(market_ix).0,
+        protocol_fees_vault: find_protocol_fees_vault_address(market_ix).0,
+        asset_mint,
+        price_update,
+        token_program,
+    }
+}
+
+pub fn buy(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, token_program: Pubkey, params: BuyOptionParams) -> Instruction {
+    build(
+        buy_accounts(signer, params.market_ix, params.page, asset_mint, price_update, token_program),
+        instruction::Buy { params },
+    )
+}
+
+pub fn buy_at_strike(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, token_program: Pubkey, params: BuyOptionAtStrikeParams) -> Instruction {
+    build(
+        buy_accounts(signer, params.market_ix, params.page, asset_mint, price_update, token_program),
+        instruction::BuyAtStrike { params },
+    )
+}
+
+//Read only, simulate it and decode the return data with `decode_quote`
+pub fn quote_option(price_update: Pubkey, params: QuoteParams) -> Instruction {
+    build(
+        accounts::QuoteOption { market: find_market_address(params.market_ix).0, price_update },
+        instruction::QuoteOption { params },
+    )
+}
+
+pub fn decode_quote(return_data: &[u8]) -> Result<OptionQuote> {
+    OptionQuote::try_from_slice(return_data).map_err(|_| ErrorCode::InstructionDidNotDeserialize.into())
+}
+
+pub fn buy_tokenized(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, token_program: Pubkey, params: BuyTokenizedParams) -> Instruction {
+    let option_series = find_option_series_address(params.market_ix, params.option, params.strike_price, params.expiry).0;
+    let option_mint = find_option_mint_address(&option_series).0;
+
+    build(
+        accounts::BuyTokenized {
+            signer,
+            user_token_acc: ata(&signer, &asset_mint, &token_program),
+            option_series,
+            option_mint,
+            user_option_ata: ata(&signer, &option_mint, &token_program),
+            market: find_market_address(params.market_ix).0,
+            market_vault: find_market_vault_address(params.market_ix).0,
+            protocol_fees_vault: find_protocol_fees_vault_address(params.market_ix).0,
+            asset_mint,
+            price_update,
+            token_program,
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::BuyTokenized { params },
+    )
+}
+
+pub fn exercise(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, settlement: Option<Pubkey>, token_program: Pubkey, params: ExerciseOptionParams) -> Instruction {
+    build(
+        accounts::ExerciseOption {
+            signer,
+            account: find_user_account_address(&signer, params.page).0,
+            user_token_acc: ata(&signer, &asset_mint, &token_program),
+            market: find_market_address(params.market_ix).0,
+            market_vault: find_market_vault_address(params.market_ix).0,
+            asset_mint,
+            price_update,
+            settlement,
+            token_program,
+        },
+        instruction::Exercise { params },
+    )
+}
+
+pub fn exercise_for(keeper: Pubkey, owner: Pubkey, asset_mint: Pubkey, price_update: Pubkey, settlement: Option<Pubkey>, token_program: Pubkey, params: ExerciseForParams) -> Instruction {
+    build(
+        accounts::ExerciseFor {
+            keeper,
+            owner,
+            account: find_user_account_address(&owner, params.page).0,
+            owner_token_acc: ata(&owner, &asset_mint, &token_program),
+            keeper_token_acc: ata(&keeper, &asset_mint, &token_program),
+            market: find_market_address(params.market_ix).0,
+            market_vault: find_market_vault_address(params.market_ix).0,
+            asset_mint,
+            price_update,
+            settlement,
+            token_program,
+        },
+        instruction::ExerciseFor { params },
+    )
+}
+
+pub fn sell_back(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, token_program: Pubkey, params: SellBackParams) -> Instruction {
+    build(
+        accounts::SellBack {
+            signer,
+            account: find_user_account_address(&signer, params.page).0,
+            user_token_acc: ata(&signer, &asset_mint, &token_program),
+            market: find_market_address(params.market_ix).0,
+            market_vault: find_market_vault_address(params.market_ix).0,
+            asset_mint,
+            price_update,
+            token_program,
+        },
+        instruction::SellBack { params },
+    )
+}
+
+pub fn exercise_tokenized(signer: Pubkey, asset_mint: Pubkey, price_update: Pubkey, settlement: Option<Pubkey>, token_program: Pubkey, params: ExerciseTokenizedParams) -> Instruction {
+    let option_series = find_option_series_address(params.market_ix, params.option, params.strike_price, params.expiry).0;
+    let option_mint = find_option_mint_address(&option_series).0;
+
+    build(
+        accounts::ExerciseTokenized {
+            signer,
+            user_token_acc: ata(&signer, &asset_mint, &token_program),
+            user_option_acc: ata(&signer, &option_mint, &token_program),
+            option_series,
+            option_mint,
+            market: find_market_address(params.market_ix).0,
+            market_vault: find_market_vault_address(params.market_ix).0,
+            asset_mint,
+            price_update,
+            settlement,
+            token_program,
+        },
+        instruction::ExerciseTokenized { params },
+    )
+}
+
+pub fn settle_expired(signer: Pubkey, owner: Pubkey, params: SettleExpiredParams) -> Instruction {
+    build(
+        accounts::SettleExpired {
+            signer,
+            owner,
+            account: find_user_account_address(&owner, params.page).0,
+            market: find_market_address(params.market_ix).0,
+        },
+        instruction::SettleExpired { params },
+    )
+}
+
+pub fn settle_expired_series(signer: Pubkey, params: SettleExpiredSeriesParams) -> Instruction {
+    build(
+        accounts::SettleExpiredSeries {
+            signer,
+            option_series: find_option_series_address(params.market_ix, params.option, params.strike_price, params.expiry).0,
+            market: find_market_address(params.market_ix).0,
+        },
+        instruction::SettleExpiredSeries { params },
+    )
+}
+
+pub fn record_settlement(signer: Pubkey, price_update: Pubkey, params: RecordSettlementParams) -> Instruction {
+    build(
+        accounts::RecordSettlement {
+            signer,
+            market: find_market_address(params.market_ix).0,
+            settlement: find_settlement_address(params.market_ix, params.expiry).0,
+            price_update,
+            system_program: system_program::ID,
+        },
+        instruction::RecordSettlement { params },
+    )
+}
+
+// --- Liquidity providers (LPs) --- //
+
+pub fn market_deposit(signer: Pubkey, asset_mint: Pubkey, token_program: Pubkey, params: DepositIx) -> Instruction {
+    let lp_mint = find_market_lp_mint_address(params.ix).0;
+
+    build(
+        accounts::MarketDeposit {
+            signer,
+            user_asset_ata: ata(&signer, &asset_mint, &token_program),
+            user_lp_ata: ata(&signer, &lp_mint, &token_program),
+            market: find_market_address(params.ix).0,
+            market_vault: find_market_vault_address(params.ix).0,
+            lp_mint,
+            asset_mint,
+            token_program,
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::MarketDeposit { params },
+    )
+}
+
+pub fn market_withdraw(signer: Pubkey, asset_mint: Pubkey, token_program: Pubkey, params: WithdrawParams) -> Instruction {
+    let lp_mint = find_market_lp_mint_address(params.ix).0;
+
+    build(
+        accounts::MarketWithdraw {
+            signer,
+            user_asset_ata: ata(&signer, &asset_mint, &token_program),
+            user_lp_ata: ata(&signer, &lp_mint, &token_program),
+            market: find_market_address(params.ix).0,
+            market_vault: find_market_vault_address(params.ix).0,
+            lp_mint,
+            asset_mint,
+            token_program,
+            system_program: system_program::ID,
+            associated_token_program: associated_token::ID,
+        },
+        instruction::MarketWithdraw { params },
+    )
+}
@@ -0,0 +1,11 @@
+//Off-chain client for the options program. Pricing and PDA logic come from the program crate itself,
+//so quotes and addresses can't drift from what the program computes
+pub mod pda;
+pub mod instructions;
+pub mod accounts;
+pub mod events;
+mod tests;
+
+pub use options_program::ID;
+pub use options_program::{common, constants, errors, state};
+pub use options_program::math::{lp_shares, premium};
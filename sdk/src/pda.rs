@@ -0,0 +1,72 @@
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::bpf_loader_upgradeable;
+use options_program::{
+    common::OptionType,
+    state::{
+        market::{MARKET_LP_MINT_SEED, MARKET_SEED, MARKET_VAULT_SEED, PROTOCOL_FEES_VAULT_SEED},
+        option_series::{OPTION_MINT_SEED, OPTION_SERIES_SEED},
+        protocol_config::PROTOCOL_CONFIG_SEED,
+        settlement::SETTLEMENT_SEED,
+        user_account::{UserAccount, USR_ACC_SEED},
+    },
+    ID,
+};
+
+//Addresses derived with the same seeds as the program account constraints
+
+pub fn find_protocol_config_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROTOCOL_CONFIG_SEED.as_bytes()], &ID)
+}
+
+//Program data account of the upgradeable program, needed to initialize the protocol
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
+
+pub fn find_market_address(market_ix: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET_SEED.as_bytes(), market_ix.to_le_bytes().as_ref()], &ID)
+}
+
+pub fn find_market_vault_address(market_ix: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET_VAULT_SEED.as_bytes(), market_ix.to_le_bytes().as_ref()], &ID)
+}
+
+pub fn find_protocol_fees_vault_address(market_ix: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PROTOCOL_FEES_VAULT_SEED.as_bytes(), market_ix.to_le_bytes().as_ref()], &ID)
+}
+
+pub fn find_market_lp_mint_address(market_ix: u16) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[MARKET_LP_MINT_SEED.as_bytes(), market_ix.to_le_bytes().as_ref()], &ID)
+}
+
+//Position page of a user account, page 0 keeps the original ["account", owner] address
+pub fn find_user_account_address(owner: &Pubkey, page: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[USR_ACC_SEED.as_bytes(), owner.as_ref(), UserAccount::page_seed(page).as_ref()],
+        &ID,
+    )
+}
+
+pub fn find_settlement_address(market_ix: u16, expiry: i64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[SETTLEMENT_SEED.as_bytes(), market_ix.to_le_bytes().as_ref(), expiry.to_le_bytes().as_ref()],
+        &ID,
+    )
+}
+
+pub fn find_option_series_address(market_ix: u16, option: OptionType, strike_price: u64, expiry: i64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[
+            OPTION_SERIES_SEED.as_bytes(),
+            market_ix.to_le_bytes().as_ref(),
+            &[u8::from(option)],
+            strike_price.to_le_bytes().as_ref(),
+            expiry.to_le_bytes().as_ref(),
+        ],
+        &ID,
+    )
+}
+
+pub fn find_option_mint_address(option_series: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[OPTION_MINT_SEED.as_bytes(), option_series.as_ref()], &ID)
+}
@@ -0,0 +1,152 @@
+#[cfg(test)]
+mod instruction_builders {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Discriminator;
+    use options_program::common::{ExpirySetting, OptionType, SpotDeviation};
+
+    use crate::instructions::*;
+    use crate::pda::*;
+
+    fn buy_params(page: u8) -> BuyOptionParams {
+        BuyOptionParams {
+            market_ix: 7,
+            page,
+            option: OptionType::CALL,
+            spot_deviation: SpotDeviation::P5,
+            expiry_setting: ExpirySetting::Daily { offset: 0 },
+            quantity: 10,
+            max_premium_tokens: 1_000_000_000,
+            max_spot_deviation: None,
+        }
+    }
+
+    #[test]
+    fn buy_targets_page_and_market_pdas() {
+        let signer = Pubkey::new_unique();
+        let ix = buy(signer, Pubkey::new_unique(), Pubkey::new_unique(), anchor_spl::token::ID, buy_params(2));
+
+        assert_eq!(ix.program_id, crate::ID);
+        assert!(ix.data.starts_with(options_program::instruction::Buy::DISCRIMINATOR));
+        assert_eq!(ix.accounts[0].pubkey, signer);
+        assert!(ix.accounts[0].is_signer);
+        assert_eq!(ix.accounts[1].pubkey, find_user_account_address(&signer, 2).0);
+        assert_eq!(ix.accounts[3].pubkey, find_market_address(7).0);
+        assert_ne!(find_user_account_address(&signer, 2).0, find_user_account_address(&signer, 0).0);
+    }
+
+    #[test]
+    fn first_page_keeps_original_address() {
+        let owner = Pubkey::new_unique();
+        let legacy = Pubkey::find_program_address(&[b"account", owner.as_ref()], &crate::ID).0;
+        assert_eq!(find_user_account_address(&owner, 0).0, legacy);
+    }
+
+    #[test]
+    fn close_account_passes_lapsed_markets_as_writable() {
+        let signer = Pubkey::new_unique();
+        let ix = close_account(signer, &[1, 4], AccountCloseParams { page: 0 });
+
+        let remaining = &ix.accounts[3..];
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[1].pubkey, find_market_address(4).0);
+        assert!(remaining.iter().all(|meta| meta.is_writable && !meta.is_signer));
+    }
+
+    #[test]
+    fn european_exercise_passes_settlement() {
+        let signer = Pubkey::new_unique();
+        let params = || ExerciseOptionParams { market_ix: 7, page: 0, option_id: 3, quantity: 1 };
+        let settlement = find_settlement_address(7, 1_792_137_600).0;
+
+        let american = exercise(signer, Pubkey::new_unique(), Pubkey::new_unique(), None, anchor_spl::token::ID, params());
+        let european = exercise(signer, Pubkey::new_unique(), Pubkey::new_unique(), Some(settlement), anchor_spl::token::ID, params());
+
+        //Anchor passes the program id for a missing optional account
+        assert_eq!(american.accounts[7].pubkey, crate::ID);
+        assert_eq!(european.accounts[7].pubkey, settlement);
+    }
+
+    #[test]
+    fn quote_round_trips_return_data() {
+        use anchor_lang::AnchorSerialize;
+
+        let quote = OptionQuote {
+            strike_price_usd: 15_000_000_000,
+            expiry: 1_792_137_600,
+            spot_price_usd: 14_522_926_200,
+            premium_usd: 123_000_000,
+            premium_tokens: 8_400_000,
+            fee_tokens: 4_200,
+            collateral_usd: 3_000_000_000,
+            collateral_tokens: 206_000_000,
+        };
+        assert_eq!(decode_quote(&quote.try_to_vec().unwrap()).unwrap(), quote);
+        assert!(decode_quote(&[1, 2, 3]).is_err());
+    }
+}
+
+#[cfg(test)]
+mod decoders {
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::{Discriminator, Event};
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use options_program::common::OptionType;
+    use options_program::state::{event::OptionBought, user_account::UserAccount};
+
+    use crate::accounts::*;
+    use crate::events::*;
+
+    #[test]
+    fn user_account_decodes_after_discriminator() {
+        let mut account: UserAccount = bytemuck::Zeroable::zeroed();
+        account.options[3].strike_price = 15_000_000_000;
+        account.options[3].quantity = 10;
+        account.options[3].is_used = 1;
+
+        let mut data = UserAccount::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&account));
+
+        //Unaligned buffer, as returned by RPC
+        let mut unaligned = vec![0_u8];
+        unaligned.extend_from_slice(&data);
+        let decoded = decode_user_account(&unaligned[1..]).unwrap();
+        assert!(decoded == account);
+
+        data[0] ^= 1;
+        assert!(decode_user_account(&data).is_err());
+        assert!(decode_user_account(&data[..16]).is_err());
+    }
+
+    #[test]
+    fn events_are_parsed_from_logs() {
+        let user = Pubkey::new_unique();
+        let event = OptionBought {
+            user,
+            market: 1,
+            page: 0,
+            option_ix: 2,
+            option: OptionType::PUT,
+            strike_price_usd: 13_000_000_000,
+            bought_at_price_usd: 14_500_000_000,
+            max_potential_payout_in_tokens: 300_000_000,
+            expiry_stamp: 1_792_137_600,
+            quantity: 10,
+        };
+
+        let logs = vec![
+            "Program 3ZWb72v75w19dvHjwsqxe6gdK3yvU6p645PPEFpCSzHg invoke [1]".to_string(),
+            "Program log: Instruction: Buy".to_string(),
+            format!("Program data: {}", STANDARD.encode(event.data())),
+            format!("Program data: {}", STANDARD.encode([0_u8; 16])),
+        ];
+
+        let events = parse_logs(&logs);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            ProgramEvent::OptionBought(bought) => {
+                assert_eq!((bought.user, bought.option_ix, bought.quantity), (user, 2, 10));
+            },
+            _ => panic!("Expected OptionBought"),
+        }
+    }
+}
@@ -16,7 +16,7 @@ describe("options-program test suite", async () => {
   const program = anchor.workspace.options_program as Program<OptionsProgram>;
   const wallet = provider.wallet as anchor.Wallet;
   console.log('Using Local Wallet: ', wallet.publicKey); 
-  console.log('The options program uses FARXLJJbSwZyELTe8TXihES7o26B2d5NKkvCkETP7Gnz as admin authority.'); 
+  console.log('The local wallet (program upgrade authority) initializes the protocol config with a generated admin.'); 
   console.log('Program Id: ', program.programId);
 
   // --- ACCOUNTS --- //
@@ -44,6 +44,14 @@ describe("options-program test suite", async () => {
   const ADMIN_START_BALANCE = 1 * LAMPORTS_PER_SOL;
 
   // --- TEST PDAs --- //
+  const [protocolConfigPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('protocol_config')],
+    program.programId
+  );
+  const [programDataPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
+    [program.programId.toBuffer()],
+    new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+  );
   const [marketPDA,] = await anchor.web3.PublicKey.findProgramAddressSync(
     [
       Buffer.from('market'),
@@ -182,17 +190,59 @@ describe("options-program test suite", async () => {
   //   }
   // })
 
+  it("Upgrade authority can initialize protocol config", async () => {
+    await program.methods
+      .initializeProtocol({ admin: admin.publicKey })
+      .accountsStrict({
+        signer: wallet.publicKey,
+        config: protocolConfigPDA,
+        program: program.programId,
+        programData: programDataPDA,
+        systemProgram: SYSTEM_PROGRAM_ID
+      })
+      .rpc();
+
+    const config = await program.account.protocolConfig.fetch(protocolConfigPDA);
+    assert(config.admin.equals(admin.publicKey), "Protocol admin should be set");
+  })
+
   it("Admin can create new market", async () => {
     const createMarketSignature = await program.methods.createMarket({
       fee: new anchor.BN(50),
       name: 'wSOL market',
       ix: marketIx,
       priceFeed: SOL_USD_PRICE_FEED_ID,
-      hour1VolatilityBps: 6000,
-      hour4VolatilityBps: 7000,
-      day1VolatilityBps: 9000,
-      day3VolatilityBps: 8000,
-      weekVolatilityBps: 5000,
+      // [expiry][strike] - rows 1H, 4H, 1D, 3D, 1W; columns 80% .. 120% of spot
+      volSurfaceBps: [
+        [7000, 6800, 6500, 6200, 6000, 5900, 5800, 5800, 5800],
+        [8000, 7800, 7500, 7200, 7000, 6900, 6800, 6800, 6800],
+        [11000, 10500, 10000, 9500, 9000, 8800, 8700, 8700, 8700],
+        [10000, 9500, 9000, 8500, 8000, 7800, 7700, 7700, 7700],
+        [6000, 5800, 5500, 5200, 5000, 4900, 4800, 4800, 4800],
+      ],
+      maxVolAgeSecs: new anchor.BN(3600),
+      minVolatilityBps: 1000,
+      maxVolatilityBps: 30000,
+      maxVolChangeBps: 5000,
+      keeperBountyBps: new anchor.BN(50),
+      pricingModel: { blackScholes: {} },
+      // Increased max price age, because the cloned pyth account has a stale price
+      maxPriceAgeBuySecs: new anchor.BN(3600),
+      maxPriceAgeExerciseSecs: new anchor.BN(3600),
+      maxPriceConfBps: new anchor.BN(100),
+      exerciseUseEma: false,
+      // $1 strike ticks for SOL
+      strikeTickSize: new anchor.BN(100_000_000),
+      minStrikeMoneynessBps: 8000,
+      maxStrikeMoneynessBps: 12000,
+      // Daily, weekly and custom expiries. Series expire at 08:00 UTC
+      expirySeries: 0b111,
+      expiryHourUtc: 8,
+      minTimeToExpirySecs: new anchor.BN(900),
+      exerciseStyle: { american: {} },
+      sellBackSpreadBps: new anchor.BN(200),
+      // One contract is 1 SOL (in lamports)
+      contractSize: new anchor.BN(1_000_000_000),
     }) 
       .accountsStrict({
         market: marketPDA,
@@ -202,6 +252,7 @@ describe("options-program test suite", async () => {
         assetMint: NATIVE_MINT,
         tokenProgram: TOKEN_PROGRAM_ID,
         signer: admin.publicKey,
+        config: protocolConfigPDA,
         systemProgram: SYSTEM_PROGRAM_ID
     })
     .signers([admin])
@@ -264,11 +315,7 @@ describe("options-program test suite", async () => {
         committed_reserve: market.committedReserve.toString(),
         premiums: market.premiums.toString(),
         lp_minted: market.lpMinted.toString(),
-        hour1VolatilityBps: market.hour1VolatilityBps,
-        hour4VolatilityBps: market.hour4VolatilityBps,
-        day1VolatilityBps: market.day1VolatilityBps,
-        day3VolatilityBps: market.day3VolatilityBps,
-        weekVolatilityBps: market.weekVolatilityBps,
+        volSurfaceBps: market.volSurfaceBps,
         price_feed: market.priceFeed,
         asset_decimals: market.assetDecimals,
       });
@@ -286,7 +333,7 @@ describe("options-program test suite", async () => {
 
     // Send the transaction to create the account
     await program.methods
-      .createAccount()
+      .createAccount({ page: 0 })
       .accountsStrict({
         signer: john.publicKey,
         account: john_taker_accountPda,
@@ -295,11 +342,32 @@ describe("options-program test suite", async () => {
       .signers([john])
       .rpc();
 
+    // Quote by simulation, priced exactly as the buy below
+    const quote = await program.methods.quoteOption({
+      marketIx: marketIx,
+      option: { call: {} },
+      strike: { spotDeviation: { deviation: { n10: {} } } },
+      expirySetting: { daily: { offset: 0 } },
+      quantity: new anchor.BN(1000),
+    }).accountsStrict({
+      market: marketPDA,
+      priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+    })
+    .view();
+
+    console.log("Quote: strike", quote.strikePriceUsd.toString(), "premium", quote.premiumTokens.toString(), "fee", quote.feeTokens.toString(), "collateral", quote.collateralTokens.toString());
+    assert(quote.premiumTokens.gt(quote.feeTokens), "Premium should include the fee");
+    assert(quote.collateralTokens.gtn(0), "Collateral should be required");
+
     const tx = await program.methods.buy({
       marketIx: marketIx,
+      page: 0,
       option: { call: {} },
       quantity: new anchor.BN(1000),
-      expirySetting: { hour4: {}},
+      // Premium cap in lamports, no guard on the oracle move off the quoted spot
+      maxPremiumTokens: new anchor.BN(10_000_000_000),
+      maxSpotDeviation: null,
+      expirySetting: { daily: { offset: 0 } },
       spotDeviation: { n10: {} } // new anchor.BN(14000000000)
     }).accountsStrict({
       account: john_taker_accountPda,
@@ -357,13 +425,16 @@ describe("options-program test suite", async () => {
 
     await program.methods.exercise({
       marketIx: marketIx,
-      optionId: 0
+      page: 0,
+      optionId: 0,
+      quantity: new anchor.BN(1000)
     }).accountsStrict({
       account: john_taker_accountPda,
       assetMint: NATIVE_MINT,
       market: marketPDA,
       marketVault: marketVaultPDA,
       priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+      settlement: null,
       signer: john.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
       userTokenAcc: john_wsol_acc,
@@ -438,9 +509,13 @@ describe("options-program test suite", async () => {
     //Buy option
     const tx = await program.methods.buy({
       marketIx: marketIx,
+      page: 0,
       option: { call: {} },
       quantity: new anchor.BN(1000),
-      expirySetting: { day1: {} },
+      // Premium cap in lamports, no guard on the oracle move off the quoted spot
+      maxPremiumTokens: new anchor.BN(10_000_000_000),
+      maxSpotDeviation: null,
+      expirySetting: { daily: { offset: 1 } },
       spotDeviation: { n10: {} } // new anchor.BN(14000000000)
     }).accountsStrict({
       account: john_taker_accountPda,
@@ -459,13 +534,16 @@ describe("options-program test suite", async () => {
     //Exercise
     await program.methods.exercise({
       marketIx: marketIx,
-      optionId: 0
+      page: 0,
+      optionId: 0,
+      quantity: new anchor.BN(1000)
     }).accountsStrict({
       account: john_taker_accountPda,
       assetMint: NATIVE_MINT,
       market: marketPDA,
       marketVault: marketVaultPDA,
       priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+      settlement: null,
       signer: john.publicKey,
       tokenProgram: TOKEN_PROGRAM_ID,
       userTokenAcc: john_wsol_acc,
@@ -489,6 +567,110 @@ describe("options-program test suite", async () => {
       });
   })
 
+  it("Takers (John) can sell an option back to the pool before expiry", async () => {
+    const [john_taker_accountPda,] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("account"), john.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods.buy({
+      marketIx: marketIx,
+      page: 0,
+      option: { put: {} },
+      quantity: new anchor.BN(1000),
+      // Premium cap in lamports, no guard on the oracle move off the quoted spot
+      maxPremiumTokens: new anchor.BN(10_000_000_000),
+      maxSpotDeviation: null,
+      expirySetting: { weekly: { offset: 0 } },
+      spotDeviation: { p0: {} }
+    }).accountsStrict({
+      account: john_taker_accountPda,
+      assetMint: NATIVE_MINT,
+      market: marketPDA,
+      marketVault: marketVaultPDA,
+      protocolFeesVault: protocolFeesVault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      userTokenAcc: john_wsol_acc,
+      priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+      signer: john.publicKey
+    })
+    .signers([john])
+    .rpc();
+
+    const marketBefore = await program.account.market.fetch(marketPDA);
+    const johnBalanceBefore = await provider.connection.getTokenAccountBalance(john_wsol_acc);
+
+    // Partial close first, the rest of the position stays open
+    await program.methods.sellBack({
+      marketIx: marketIx,
+      page: 0,
+      optionId: 0,
+      quantity: new anchor.BN(400),
+      minAmountOut: new anchor.BN(0)
+    }).accountsStrict({
+      account: john_taker_accountPda,
+      assetMint: NATIVE_MINT,
+      market: marketPDA,
+      marketVault: marketVaultPDA,
+      priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+      signer: john.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      userTokenAcc: john_wsol_acc,
+    })
+    .signers([john]).rpc();
+
+    const partial = (await program.account.userAccount.fetch(john_taker_accountPda)).options[0];
+    assert(partial.quantity.toNumber() === 600, "Remaining quantity should stay open");
+
+    await program.methods.sellBack({
+      marketIx: marketIx,
+      page: 0,
+      optionId: 0,
+      quantity: new anchor.BN(600),
+      minAmountOut: new anchor.BN(0)
+    }).accountsStrict({
+      account: john_taker_accountPda,
+      assetMint: NATIVE_MINT,
+      market: marketPDA,
+      marketVault: marketVaultPDA,
+      priceUpdate: new PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE"),
+      signer: john.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      userTokenAcc: john_wsol_acc,
+    })
+    .signers([john]).rpc();
+
+    const john_accountData = await program.account.userAccount.fetch(john_taker_accountPda);
+    const opt = john_accountData.options[0];
+    assert(opt.quantity.toNumber() === 0, "Option not in cleared state");
+
+    const marketAfter = await program.account.market.fetch(marketPDA);
+    const johnBalanceAfter = await provider.connection.getTokenAccountBalance(john_wsol_acc);
+    assert(Number(johnBalanceAfter.value.amount) > Number(johnBalanceBefore.value.amount), "John should receive the option value");
+    assert(marketAfter.committedReserve.lt(marketBefore.committedReserve), "Collateral should be released");
+  });
+
+  it("Takers (John) can close his account and reclaim rent", async () => {
+    const [john_taker_accountPda,] = await anchor.web3.PublicKey.findProgramAddress(
+      [Buffer.from("account"), john.publicKey.toBuffer()],
+      program.programId
+    );
+    const johnLamportsBefore = await provider.connection.getBalance(john.publicKey);
+
+    await program.methods.closeAccount({ page: 0 })
+      .accountsStrict({
+        signer: john.publicKey,
+        account: john_taker_accountPda,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([john])
+      .rpc();
+
+    const closed = await provider.connection.getAccountInfo(john_taker_accountPda);
+    assert(closed === null, "Account should be closed");
+    assert(await provider.connection.getBalance(john.publicKey) > johnLamportsBefore, "Rent should be returned to John");
+  });
+
   it("Depositors (Bob and Alice) withdraw", async () => {
     let market = await program.account.market.fetch(marketPDA);
     console.log("f - Market Account:");
@@ -565,7 +747,7 @@ describe("options-program test suite", async () => {
     assert(Number(bobwSOLbalance.value.amount) > DEPOSIT_AMOUNT, "Bob should have more than 1000 wSOL tokens");
   })
 
-  it("Admin can close market. Should receive protocol fees. Associated market PDAs must be closed", async () => {
+  it("Admin withdraws protocol fees and closes the market. Associated market PDAs must be closed", async () => {
     let market = await program.account.market.fetch(marketPDA);
     let marketVault = await provider.connection.getTokenAccountBalance(marketVaultPDA);
     let protocolFees = await provider.connection.getTokenAccountBalance(protocolFeesVault);
@@ -602,10 +784,26 @@ describe("options-program test suite", async () => {
 
     }
 
+    //close_market rejects a fee vault with a balance, fees leave through withdraw_fees
+    await program.methods
+      .withdrawFees({ix: marketIx})
+      .accountsStrict({
+        admin: admin.publicKey,
+        config: protocolConfigPDA,
+        adminTokenAcc: adminAta,
+        assetMint: NATIVE_MINT,
+        protocolFeesVault: protocolFeesVault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: SYSTEM_PROGRAM_ID,
+      })
+      .signers([admin])
+      .rpc();
+
     const res = await program.methods
       .closeMarket({ix: marketIx})
       .accountsStrict({
         admin: admin.publicKey,
+        config: protocolConfigPDA,
         assetMint: NATIVE_MINT,
         lpMint: lpMintPDA,
         market: marketPDA,